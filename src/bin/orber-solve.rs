@@ -0,0 +1,94 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process,
+};
+
+use bevy_github_ci_template::{
+    level::{Campaign, Level, CAMPAIGN_PATH},
+    orb::Orb,
+    solver,
+};
+
+// same lookup the bevy asset server does, so `cargo run` works from anywhere in the repo
+fn assets_dir() -> PathBuf {
+    env::var("CARGO_MANIFEST_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_default()
+        .join("assets")
+}
+
+fn read_ron<T: serde::de::DeserializeOwned>(path: &Path) -> Result<T, String> {
+    let bytes = fs::read(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    ron::de::from_bytes(&bytes).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+fn describe(orb: &Orb) -> String {
+    if orb.children.is_empty() {
+        format!("{}", orb)
+    } else {
+        let children: Vec<String> = orb.children.iter().map(describe).collect();
+        format!("{} = ({})", orb, children.join(" + "))
+    }
+}
+
+fn main() {
+    let mut paths: Vec<PathBuf> = env::args().skip(1).map(PathBuf::from).collect();
+    if paths.is_empty() {
+        let campaign: Campaign = read_ron(&assets_dir().join(CAMPAIGN_PATH)).unwrap_or_else(|e| {
+            eprintln!("could not read campaign: {}", e);
+            process::exit(2);
+        });
+        paths = campaign
+            .levels
+            .iter()
+            .map(|path| assets_dir().join(path))
+            .collect();
+    }
+
+    let mut all_winnable = true;
+    for path in paths {
+        let level: Level = match read_ron(&path) {
+            Ok(level) => level,
+            Err(e) => {
+                eprintln!("could not read level: {}", e);
+                process::exit(2);
+            }
+        };
+
+        let player_orbs: Vec<Orb> = level.player_orbs.iter().map(|o| o.orb.clone()).collect();
+        let enemy_orbs: Vec<Orb> = level.enemy_orbs.iter().map(|o| o.orb.clone()).collect();
+
+        println!("{} ({})", level.name, path.display());
+        let report =
+            match solver::solve(&player_orbs, &enemy_orbs, level.merge_rule, level.stat_caps) {
+                Ok(report) => report,
+                Err(e) => {
+                    all_winnable = false;
+                    println!("  could not solve: {}", e);
+                    continue;
+                }
+            };
+        match &report.minimal {
+            Some(minimal) => {
+                println!("  winnable");
+                println!("  minimal winning line-up (actions: {}):", minimal.actions);
+                for (i, orb) in minimal.line_up.iter().enumerate() {
+                    println!("    {}. {}", i + 1, describe(orb));
+                }
+            }
+            None => {
+                all_winnable = false;
+                println!("  NOT winnable");
+            }
+        }
+        println!(
+            "  {} distinct winning line-ups out of {} checked",
+            report.solutions, report.line_ups_checked
+        );
+    }
+
+    if !all_winnable {
+        process::exit(1);
+    }
+}
@@ -0,0 +1,861 @@
+use std::{collections::VecDeque, fmt};
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+use bevy_easings::Lerp;
+
+use crate::{
+    combat_log::CombatLog,
+    combat_speed::CombatSpeed,
+    effects::{self, Hit},
+    formation::InSlot,
+    hud::{StartMenu, TextDetails},
+    level::{CampaignLevels, ChangeLevel, CurrentLevel, Endless, Level, LevelCompleted},
+    level_select::LevelSelect,
+    orb::{EnemyOrb, Orb, PlayerOrb, Stat},
+    orb_animation::Animating,
+    pause::{on_update_unpaused, unpaused},
+    save::{LevelResult, SaveData},
+    BasePlugin, ENEMY_COLOR, PLAYER_COLOR,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Side {
+    Player,
+    Enemy,
+}
+
+// indices always refer to the slices passed to `resolve`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BattleEvent {
+    Exchange {
+        player: usize,
+        enemy: usize,
+        player_health: Stat,
+        enemy_health: Stat,
+    },
+    Died {
+        side: Side,
+        index: usize,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    PlayerWon,
+    EnemyWon,
+    // neither front orb can hurt the other, so the fight would never end
+    Stalemate,
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Outcome::PlayerWon => write!(f, "Player wins"),
+            Outcome::EnemyWon => write!(f, "Enemy wins"),
+            Outcome::Stalemate => write!(f, "Stalemate, neither front orb can do damage"),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct BattleLog {
+    pub events: Vec<BattleEvent>,
+    pub outcome: Outcome,
+    // player orbs left standing with their remaining health, front of the line first
+    pub survivors: Vec<Orb>,
+}
+
+impl BattleLog {
+    pub fn won(&self) -> bool {
+        self.outcome == Outcome::PlayerWon
+    }
+
+    /// Spells out each event for people, one entry per event, e.g.
+    /// "Orb(4/1) hits Enemy(8/1) for 4, Enemy at 4". Takes the same line-ups
+    /// the log was resolved from.
+    pub fn describe(&self, players: &[Orb], enemies: &[Orb]) -> Vec<Vec<String>> {
+        let mut player_states = players.to_vec();
+        let mut enemy_states = enemies.to_vec();
+
+        self.events
+            .iter()
+            .map(|event| match *event {
+                BattleEvent::Exchange {
+                    player,
+                    enemy,
+                    player_health,
+                    enemy_health,
+                } => {
+                    let (p, e) = (&player_states[player], &enemy_states[enemy]);
+                    let lines = vec![
+                        format!(
+                            "Orb({}) hits Enemy({}) for {}, Enemy at {}",
+                            p, e, p.damage, enemy_health
+                        ),
+                        format!(
+                            "Enemy({}) hits Orb({}) for {}, Orb at {}",
+                            e, p, e.damage, player_health
+                        ),
+                    ];
+                    player_states[player].health = player_health;
+                    enemy_states[enemy].health = enemy_health;
+                    lines
+                }
+                BattleEvent::Died { side, index } => vec![match side {
+                    Side::Player => format!("Orb({}) dies", player_states[index]),
+                    Side::Enemy => format!("Enemy({}) dies", enemy_states[index]),
+                }],
+            })
+            .collect()
+    }
+}
+
+/// Plays out a battle between two lines of orbs, front of the line first.
+///
+/// The front orbs hit each other at the same time and anything at or below
+/// zero health leaves the line, until one of the lines is empty. Emptying
+/// both lines at once counts as a win for the player.
+pub fn resolve(players: &[Orb], enemies: &[Orb]) -> BattleLog {
+    let mut player_states = players.to_vec();
+    let mut enemy_states = enemies.to_vec();
+    let mut player_line: VecDeque<usize> = (0..players.len()).collect();
+    let mut enemy_line: VecDeque<usize> = (0..enemies.len()).collect();
+    let mut events = vec![];
+
+    let mut outcome = None;
+    while let (Some(&p), Some(&e)) = (player_line.front(), enemy_line.front()) {
+        let player_damage = player_states[p].damage;
+        let enemy_damage = enemy_states[e].damage;
+
+        if player_damage <= Stat(0) && enemy_damage <= Stat(0) {
+            outcome = Some(Outcome::Stalemate);
+            break;
+        }
+
+        let player_orb = &mut player_states[p];
+        player_orb.health -= enemy_damage;
+        let enemy_orb = &mut enemy_states[e];
+        enemy_orb.health -= player_damage;
+
+        events.push(BattleEvent::Exchange {
+            player: p,
+            enemy: e,
+            player_health: player_states[p].health,
+            enemy_health: enemy_states[e].health,
+        });
+
+        if player_states[p].health <= Stat(0) {
+            events.push(BattleEvent::Died {
+                side: Side::Player,
+                index: p,
+            });
+            player_line.pop_front();
+        }
+        if enemy_states[e].health <= Stat(0) {
+            events.push(BattleEvent::Died {
+                side: Side::Enemy,
+                index: e,
+            });
+            enemy_line.pop_front();
+        }
+    }
+
+    let outcome = outcome.unwrap_or(if enemy_line.is_empty() {
+        Outcome::PlayerWon
+    } else {
+        Outcome::EnemyWon
+    });
+
+    BattleLog {
+        events,
+        outcome,
+        survivors: player_line
+            .iter()
+            .map(|&p| player_states[p].clone())
+            .collect(),
+    }
+}
+
+#[derive(Clone)]
+pub struct CombatSettings {
+    // distance from the centre to the front of each line
+    pub line_start: f32,
+    pub line_spacing: f32,
+}
+
+impl Default for CombatSettings {
+    fn default() -> Self {
+        CombatSettings {
+            line_start: 150.0,
+            line_spacing: 120.0,
+        }
+    }
+}
+
+impl CombatSettings {
+    pub fn player_x(&self, index: usize) -> f32 {
+        -self.line_start - index as f32 * self.line_spacing
+    }
+
+    pub fn enemy_x(&self, index: usize) -> f32 {
+        self.line_start + index as f32 * self.line_spacing
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Phase {
+    PREP,
+    COMBAT,
+}
+
+// only leaves `Idle` while `Phase::COMBAT` is active
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum CombatStep {
+    Idle,
+    LineUp,
+    Attack,
+    Next,
+}
+
+pub struct OrbDamaged {
+    pub entity: Entity,
+    pub side: Side,
+    pub damage: Stat,
+    // left after the hit, at or below zero means it's about to die
+    pub health: Stat,
+}
+
+// sent just before the orb is despawned
+pub struct OrbDied {
+    pub entity: Entity,
+    pub side: Side,
+    pub orb: Orb,
+}
+
+pub struct CombatFinished {
+    pub won: bool,
+}
+
+#[derive(SystemParam)]
+struct CombatEvents<'w, 's> {
+    change_level: EventWriter<'w, 's, ChangeLevel>,
+    damaged: EventWriter<'w, 's, OrbDamaged>,
+    died: EventWriter<'w, 's, OrbDied>,
+    finished: EventWriter<'w, 's, CombatFinished>,
+    level_completed: EventWriter<'w, 's, LevelCompleted>,
+}
+
+// entities lined up for the current battle, and how far its log has been animated
+#[derive(Default)]
+pub struct Battle {
+    players: Vec<Entity>,
+    enemies: Vec<Entity>,
+    log: Option<BattleLog>,
+    next_event: usize,
+    last_exchange: Option<(usize, usize)>,
+    // plays out the rest of the log in one go
+    pub skip: bool,
+}
+
+type PlayerCombatQuery<'w, 's> =
+    Query<'w, 's, (&'static mut Transform, &'static mut Orb), (With<PlayerOrb>, Without<EnemyOrb>)>;
+type EnemyCombatQuery<'w, 's> =
+    Query<'w, 's, (&'static mut Transform, &'static mut Orb), (With<EnemyOrb>, Without<PlayerOrb>)>;
+type MenuQuery<'w, 's> = Query<'w, 's, (), Or<(With<StartMenu>, With<LevelSelect>)>>;
+
+// one side's orbs in the order they step up to fight
+pub type BattleLine = Vec<(Entity, Orb)>;
+
+// player orbs fight in slot order, with any orb left out of the formation queued behind
+// them right-most first. Enemies fight left-most first
+pub fn battle_lines(
+    player_orb_query: &Query<(Entity, &Transform, &Orb, Option<&InSlot>), With<PlayerOrb>>,
+    enemy_orb_query: &Query<(Entity, &Transform, &Orb), With<EnemyOrb>>,
+) -> (BattleLine, BattleLine) {
+    let mut players: Vec<(Entity, f32, Orb, Option<InSlot>)> = player_orb_query
+        .iter()
+        .map(|(e, t, orb, slot)| (e, t.translation.x, orb.clone(), slot.copied()))
+        .collect();
+    players.sort_by(|a, b| {
+        let slot =
+            |p: &(Entity, f32, Orb, Option<InSlot>)| p.3.map_or(usize::MAX, |InSlot(index)| index);
+        slot(a)
+            .cmp(&slot(b))
+            .then(b.1.total_cmp(&a.1))
+            .then(a.0.cmp(&b.0))
+    });
+
+    let mut enemies: Vec<(Entity, f32, Orb)> = enemy_orb_query
+        .iter()
+        .map(|(e, t, orb)| (e, t.translation.x, orb.clone()))
+        .collect();
+    enemies.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+    (
+        players.into_iter().map(|(e, _, orb, _)| (e, orb)).collect(),
+        enemies.into_iter().map(|(e, _, orb)| (e, orb)).collect(),
+    )
+}
+
+fn start_battle(
+    mut battle: ResMut<Battle>,
+    mut combat_step: ResMut<State<CombatStep>>,
+    mut combat_log: ResMut<CombatLog>,
+    player_orb_query: Query<(Entity, &Transform, &Orb, Option<&InSlot>), With<PlayerOrb>>,
+    enemy_orb_query: Query<(Entity, &Transform, &Orb), With<EnemyOrb>>,
+    current_level: Res<CurrentLevel>,
+    campaign_levels: Res<CampaignLevels>,
+    levels: Res<Assets<Level>>,
+) {
+    let (players, enemies) = battle_lines(&player_orb_query, &enemy_orb_query);
+    let player_orbs: Vec<Orb> = players.iter().map(|(_, orb)| orb.clone()).collect();
+    let enemy_orbs: Vec<Orb> = enemies.iter().map(|(_, orb)| orb.clone()).collect();
+
+    let log = resolve(&player_orbs, &enemy_orbs);
+    let level_name = campaign_levels
+        .get(current_level.0, &levels)
+        .map(|level| level.name.as_str())
+        .unwrap_or_default();
+    combat_log.start(
+        format!("Level {} - {}", current_level.0 + 1, level_name),
+        log.describe(&player_orbs, &enemy_orbs),
+    );
+
+    *battle = Battle {
+        players: players.iter().map(|(e, _)| *e).collect(),
+        enemies: enemies.iter().map(|(e, _)| *e).collect(),
+        log: Some(log),
+        ..default()
+    };
+    combat_step.set(CombatStep::LineUp).unwrap();
+}
+
+fn end_battle(
+    mut battle: ResMut<Battle>,
+    mut combat_step: ResMut<State<CombatStep>>,
+    mut speed: ResMut<CombatSpeed>,
+    mut combat_log: ResMut<CombatLog>,
+) {
+    *battle = Battle::default();
+    speed.clear_steps();
+    combat_log.cancel();
+    // a step change may already be queued if combat was cancelled mid-exchange
+    let _ = combat_step.overwrite_set(CombatStep::Idle);
+}
+
+fn combat_line_up(
+    mut combat_step: ResMut<State<CombatStep>>,
+    battle: Res<Battle>,
+    mut speed: ResMut<CombatSpeed>,
+    time: Res<Time>,
+    mut player_orb_query: PlayerCombatQuery,
+    settings: Res<CombatSettings>,
+) {
+    let fraction = speed.fraction(0.2, time.delta_seconds());
+    let mut completed_orbs: Vec<bool> = vec![];
+    for (i, &entity) in battle.players.iter().enumerate() {
+        if let Ok((mut t, _)) = player_orb_query.get_mut(entity) {
+            let target_x = settings.player_x(i);
+            t.translation.y = t.translation.y.lerp(&0., &fraction);
+            t.translation.x = t.translation.x.lerp(&target_x, &fraction);
+
+            completed_orbs
+                .push(t.translation.y.abs() < 0.1 && (t.translation.x - target_x).abs() < 0.1);
+        }
+    }
+    if battle.skip || (completed_orbs.iter().all(|o| *o) && speed.take_step()) {
+        combat_step.set(CombatStep::Attack).unwrap();
+    }
+}
+
+fn combat_attack(
+    mut phase: ResMut<State<Phase>>,
+    mut combat_step: ResMut<State<CombatStep>>,
+    mut battle: ResMut<Battle>,
+    speed: Res<CombatSpeed>,
+    time: Res<Time>,
+    mut player_orb_query: PlayerCombatQuery,
+    mut enemy_orb_query: EnemyCombatQuery,
+    mut commands: Commands,
+    mut events: CombatEvents,
+    current_level: Res<CurrentLevel>,
+    campaign_levels: Res<CampaignLevels>,
+    mut save: ResMut<SaveData>,
+    mut combat_log: ResMut<CombatLog>,
+    text_details: Res<TextDetails>,
+) {
+    let battle = &mut *battle;
+    let log = match &battle.log {
+        Some(log) => log,
+        None => return,
+    };
+    let fraction = speed.fraction(0.2, time.delta_seconds());
+
+    // skipping keeps going until the log runs out, otherwise one event per frame
+    loop {
+        match log.events.get(battle.next_event) {
+            Some(&BattleEvent::Exchange {
+                player,
+                enemy,
+                player_health,
+                enemy_health,
+                ..
+            }) => {
+                let mut player_reached = battle.skip;
+                if let Ok((mut t, _)) = player_orb_query.get_mut(battle.players[player]) {
+                    let target_x = -30.;
+                    let v = t.translation.x - target_x;
+
+                    t.translation.x -= v * fraction;
+
+                    if v <= 0.5 {
+                        player_reached = true;
+                    }
+                }
+
+                let mut enemy_reached = battle.skip;
+                if let Ok((mut t, _)) = enemy_orb_query.get_mut(battle.enemies[enemy]) {
+                    let target_x = 50.;
+                    let v = t.translation.x - target_x;
+
+                    t.translation.x -= v * fraction;
+
+                    if v <= 0.5 {
+                        enemy_reached = true;
+                    }
+                }
+
+                if enemy_reached && player_reached {
+                    if let Ok((t, mut player_orb)) =
+                        player_orb_query.get_mut(battle.players[player])
+                    {
+                        let damage = player_orb.health - player_health;
+                        commands.entity(battle.players[player]).insert(Hit {
+                            base_color: PLAYER_COLOR,
+                        });
+                        effects::spawn_damage_number(
+                            &mut commands,
+                            &text_details,
+                            t.translation,
+                            damage,
+                        );
+                        events.damaged.send(OrbDamaged {
+                            entity: battle.players[player],
+                            side: Side::Player,
+                            damage,
+                            health: player_health,
+                        });
+                        player_orb.health = player_health;
+                    }
+                    if let Ok((t, mut enemy_orb)) = enemy_orb_query.get_mut(battle.enemies[enemy]) {
+                        let damage = enemy_orb.health - enemy_health;
+                        commands.entity(battle.enemies[enemy]).insert(Hit {
+                            base_color: ENEMY_COLOR,
+                        });
+                        effects::spawn_damage_number(
+                            &mut commands,
+                            &text_details,
+                            t.translation,
+                            damage,
+                        );
+                        events.damaged.send(OrbDamaged {
+                            entity: battle.enemies[enemy],
+                            side: Side::Enemy,
+                            damage,
+                            health: enemy_health,
+                        });
+                        enemy_orb.health = enemy_health;
+                    }
+
+                    battle.next_event += 1;
+                    while let Some(&BattleEvent::Died { side, index }) =
+                        log.events.get(battle.next_event)
+                    {
+                        despawn_dead_orb(
+                            &mut commands,
+                            &mut events.died,
+                            battle,
+                            side,
+                            index,
+                            &player_orb_query,
+                            &enemy_orb_query,
+                        );
+                        battle.next_event += 1;
+                    }
+
+                    battle.last_exchange = Some((player, enemy));
+                    if !battle.skip {
+                        combat_step.set(CombatStep::Next).unwrap();
+                    }
+                }
+            }
+            Some(&BattleEvent::Died { side, index }) => {
+                despawn_dead_orb(
+                    &mut commands,
+                    &mut events.died,
+                    battle,
+                    side,
+                    index,
+                    &player_orb_query,
+                    &enemy_orb_query,
+                );
+                battle.next_event += 1;
+            }
+            None => {
+                let level = current_level.0;
+                if log.won() {
+                    let result = LevelResult {
+                        survivors: log.survivors.len(),
+                        remaining_health: log.survivors.iter().map(|o| o.health).sum(),
+                    };
+                    if !campaign_levels.is_generated(level) {
+                        save.record_win(level, result);
+                    }
+                    events
+                        .level_completed
+                        .send(LevelCompleted { level, result });
+                }
+                events.finished.send(CombatFinished { won: log.won() });
+                events.change_level.send(if log.won() {
+                    ChangeLevel::Next
+                } else {
+                    ChangeLevel::Restart
+                });
+                combat_log.finish(log.outcome);
+                let _ = phase.set(Phase::PREP);
+                break;
+            }
+        }
+        combat_log.reveal(battle.next_event);
+
+        if !battle.skip {
+            break;
+        }
+    }
+}
+
+// takes a dead orb off the board, bursting into shards where it stood
+fn despawn_dead_orb(
+    commands: &mut Commands,
+    died_writer: &mut EventWriter<OrbDied>,
+    battle: &Battle,
+    side: Side,
+    index: usize,
+    player_orb_query: &PlayerCombatQuery,
+    enemy_orb_query: &EnemyCombatQuery,
+) {
+    let (entity, dead, color) = match side {
+        Side::Player => {
+            let entity = battle.players[index];
+            (
+                entity,
+                player_orb_query
+                    .get(entity)
+                    .map(|(t, orb)| (t.translation, orb)),
+                PLAYER_COLOR,
+            )
+        }
+        Side::Enemy => {
+            let entity = battle.enemies[index];
+            (
+                entity,
+                enemy_orb_query
+                    .get(entity)
+                    .map(|(t, orb)| (t.translation, orb)),
+                ENEMY_COLOR,
+            )
+        }
+    };
+    if let Ok((position, orb)) = dead {
+        effects::spawn_death_burst(commands, position, color);
+        died_writer.send(OrbDied {
+            entity,
+            side,
+            orb: orb.clone(),
+        });
+    }
+    commands.entity(entity).despawn_recursive();
+}
+
+fn combat_next(
+    mut combat_step: ResMut<State<CombatStep>>,
+    battle: Res<Battle>,
+    mut speed: ResMut<CombatSpeed>,
+    time: Res<Time>,
+    mut player_orb_query: PlayerCombatQuery,
+    mut enemy_orb_query: EnemyCombatQuery,
+    settings: Res<CombatSettings>,
+) {
+    let mut completed_orbs: Vec<bool> = vec![];
+    let fraction = speed.fraction(0.1, time.delta_seconds());
+
+    if let Some((player, enemy)) = battle.last_exchange {
+        if let Ok((mut t, _)) = player_orb_query.get_mut(battle.players[player]) {
+            let target_x = settings.player_x(player);
+
+            t.translation.x -= (t.translation.x - target_x) * fraction;
+
+            completed_orbs.push((t.translation.x - target_x).abs() <= 2.0);
+        }
+
+        if let Ok((mut t, _)) = enemy_orb_query.get_mut(battle.enemies[enemy]) {
+            let target_x = settings.enemy_x(enemy);
+
+            t.translation.x -= (t.translation.x - target_x) * fraction;
+
+            completed_orbs.push((t.translation.x - target_x).abs() <= 2.0);
+        }
+    }
+
+    if battle.skip || (completed_orbs.iter().all(|o| *o) && speed.take_step()) {
+        combat_step.set(CombatStep::Attack).unwrap();
+    }
+}
+
+fn switch_phase_listener(
+    mut phase: ResMut<State<Phase>>,
+    input: Res<Input<KeyCode>>,
+    current_level: Res<CurrentLevel>,
+    mut change_level_writer: EventWriter<ChangeLevel>,
+    campaign_levels: Res<CampaignLevels>,
+    mut endless: ResMut<Endless>,
+    menu_query: MenuQuery,
+    animating_query: Query<(), With<Animating>>,
+) {
+    if !menu_query.is_empty() {
+        return;
+    }
+
+    if input.just_pressed(KeyCode::E) && campaign_levels.is_complete(current_level.0) {
+        endless.enabled = true;
+        endless.first_level = campaign_levels.levels.len();
+        change_level_writer.send(ChangeLevel::Goto(campaign_levels.levels.len()));
+    }
+
+    // instant retry, also bails out of a running combat
+    if input.just_pressed(KeyCode::R) && !campaign_levels.is_complete(current_level.0) {
+        if *phase.current() == Phase::COMBAT {
+            let _ = phase.set(Phase::PREP);
+        }
+        change_level_writer.send(ChangeLevel::Restart);
+    }
+
+    if input.just_pressed(KeyCode::Space) {
+        if campaign_levels.is_complete(current_level.0) {
+            change_level_writer.send(ChangeLevel::Goto(0));
+        } else if animating_query.is_empty() {
+            // orbs still merging or splitting would miss the battle
+            let next = match phase.current() {
+                Phase::PREP => Phase::COMBAT,
+                Phase::COMBAT => Phase::PREP,
+            };
+            let _ = phase.set(next);
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct CombatPlugin {
+    pub settings: CombatSettings,
+}
+
+impl Plugin for CombatPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(BasePlugin)
+            .insert_resource(self.settings.clone())
+            .add_state(CombatStep::Idle)
+            .add_event::<OrbDamaged>()
+            .add_event::<OrbDied>()
+            .add_event::<CombatFinished>()
+            .add_system(switch_phase_listener.with_run_criteria(unpaused))
+            .add_system_set(SystemSet::on_enter(Phase::COMBAT).with_system(start_battle))
+            .add_system_set(SystemSet::on_exit(Phase::COMBAT).with_system(end_battle))
+            .add_system_set(
+                SystemSet::new()
+                    .with_run_criteria(on_update_unpaused(CombatStep::LineUp))
+                    .with_system(combat_line_up),
+            )
+            .add_system_set(
+                SystemSet::new()
+                    .with_run_criteria(on_update_unpaused(CombatStep::Attack))
+                    .with_system(combat_attack),
+            )
+            .add_system_set(
+                SystemSet::new()
+                    .with_run_criteria(on_update_unpaused(CombatStep::Next))
+                    .with_system(combat_next),
+            );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::SystemState;
+
+    use super::*;
+
+    fn orb(damage: i32, health: i32) -> Orb {
+        Orb {
+            damage: Stat(damage),
+            health: Stat(health),
+            ..Orb::default()
+        }
+    }
+
+    // the level 3 line-up, the cursed 3/-2 fights on below zero health until it's hit
+    fn cursed() -> (Vec<Orb>, Vec<Orb>) {
+        (vec![orb(2, 3), orb(3, -2)], vec![orb(3, 5)])
+    }
+
+    #[test]
+    fn emptying_both_lines_at_once_is_a_player_win() {
+        let log = resolve(&[orb(1, 1)], &[orb(1, 1)]);
+        assert_eq!(log.outcome, Outcome::PlayerWon);
+        assert!(log.won());
+        assert!(log.survivors.is_empty());
+        assert_eq!(
+            log.events,
+            vec![
+                BattleEvent::Exchange {
+                    player: 0,
+                    enemy: 0,
+                    player_health: Stat(0),
+                    enemy_health: Stat(0),
+                },
+                BattleEvent::Died {
+                    side: Side::Player,
+                    index: 0,
+                },
+                BattleEvent::Died {
+                    side: Side::Enemy,
+                    index: 0,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn survivors_keep_their_remaining_health() {
+        let log = resolve(&[orb(5, 5), orb(1, 1)], &[orb(1, 1)]);
+        assert!(log.won());
+        let survivors: Vec<(i32, i32)> = log
+            .survivors
+            .iter()
+            .map(|o| (o.damage.0, o.health.0))
+            .collect();
+        assert_eq!(survivors, vec![(5, 4), (1, 1)]);
+
+        let log = resolve(&[orb(1, 1)], &[orb(1, 2)]);
+        assert_eq!(log.outcome, Outcome::EnemyWon);
+        assert!(log.survivors.is_empty());
+    }
+
+    #[test]
+    fn front_orbs_without_damage_are_a_stalemate() {
+        let log = resolve(&[orb(0, 5)], &[orb(-1, 3)]);
+        assert_eq!(log.outcome, Outcome::Stalemate);
+        assert!(!log.won());
+        assert!(log.events.is_empty());
+
+        // only the front orbs count, the ones behind never get to fight
+        let log = resolve(&[orb(1, 1), orb(0, 5), orb(9, 9)], &[orb(1, 1), orb(0, 3)]);
+        assert_eq!(log.outcome, Outcome::Stalemate);
+        assert_eq!(log.events.len(), 3);
+    }
+
+    #[test]
+    fn negative_health_orbs_fight_until_hit() {
+        let (players, enemies) = cursed();
+        let log = resolve(&players, &enemies);
+        assert!(log.won());
+        assert_eq!(
+            log.events[2],
+            BattleEvent::Exchange {
+                player: 1,
+                enemy: 0,
+                player_health: Stat(-5),
+                enemy_health: Stat(0),
+            }
+        );
+
+        // in front it still lands one hit before dying
+        let log = resolve(&[orb(3, -2), orb(2, 3)], &enemies);
+        assert!(log.won());
+        assert_eq!(
+            log.events[..2],
+            [
+                BattleEvent::Exchange {
+                    player: 0,
+                    enemy: 0,
+                    player_health: Stat(-5),
+                    enemy_health: Stat(2),
+                },
+                BattleEvent::Died {
+                    side: Side::Player,
+                    index: 0,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn describe_spells_out_each_event() {
+        let (players, enemies) = cursed();
+        let log = resolve(&players, &enemies);
+        assert_eq!(
+            log.describe(&players, &enemies),
+            vec![
+                vec![
+                    "Orb(2/3) hits Enemy(3/5) for 2, Enemy at 3",
+                    "Enemy(3/5) hits Orb(2/3) for 3, Orb at 0",
+                ],
+                vec!["Orb(2/0) dies"],
+                vec![
+                    "Orb(3/-2) hits Enemy(3/3) for 3, Enemy at 0",
+                    "Enemy(3/3) hits Orb(3/-2) for 3, Orb at -5",
+                ],
+                vec!["Orb(3/-5) dies"],
+                vec!["Enemy(3/0) dies"],
+            ]
+        );
+    }
+
+    #[test]
+    fn battle_lines_follow_slots_then_position() {
+        let mut world = World::new();
+        let mut spawn_player = |x: f32, slot: Option<usize>| {
+            let mut entity = world.spawn();
+            entity.insert_bundle((Transform::from_xyz(x, 0., 0.), orb(1, 1), PlayerOrb));
+            if let Some(index) = slot {
+                entity.insert(InSlot(index));
+            }
+            entity.id()
+        };
+        let second = spawn_player(-230., Some(1));
+        let first = spawn_player(-150., Some(0));
+        let last = spawn_player(100., None);
+        let third = spawn_player(300., None);
+
+        let mut spawn_enemy = |x: f32| {
+            world
+                .spawn()
+                .insert_bundle((Transform::from_xyz(x, 0., 0.), orb(1, 1), EnemyOrb))
+                .id()
+        };
+        let middle = spawn_enemy(300.);
+        let front = spawn_enemy(150.);
+        let back = spawn_enemy(450.);
+
+        let mut state: SystemState<(
+            Query<(Entity, &Transform, &Orb, Option<&InSlot>), With<PlayerOrb>>,
+            Query<(Entity, &Transform, &Orb), With<EnemyOrb>>,
+        )> = SystemState::new(&mut world);
+        let (player_orb_query, enemy_orb_query) = state.get(&world);
+        let (players, enemies) = battle_lines(&player_orb_query, &enemy_orb_query);
+
+        let players: Vec<Entity> = players.into_iter().map(|(e, _)| e).collect();
+        let enemies: Vec<Entity> = enemies.into_iter().map(|(e, _)| e).collect();
+        assert_eq!(players, vec![first, second, third, last]);
+        assert_eq!(enemies, vec![front, middle, back]);
+    }
+}
@@ -0,0 +1,218 @@
+use bevy::{input::mouse::MouseWheel, prelude::*};
+
+use crate::{
+    combat::Outcome,
+    hud::{set_panel_visible, setup_text_details, TextDetails},
+    BasePlugin,
+};
+
+const LOG_FILE: &str = "combat_log.txt";
+const VISIBLE_LINES: usize = 14;
+
+// the last battle in words, kept after it ends so it can still be copied or saved
+#[derive(Default)]
+pub struct CombatLog {
+    // what each event of the battle reads as, revealed as the animation gets to it
+    entries: Vec<Vec<String>>,
+    revealed: usize,
+    finished: bool,
+    lines: Vec<String>,
+}
+
+impl CombatLog {
+    pub fn start(&mut self, title: String, entries: Vec<Vec<String>>) {
+        *self = CombatLog {
+            entries,
+            lines: vec![title],
+            ..default()
+        };
+    }
+
+    pub fn reveal(&mut self, events: usize) {
+        let events = events.min(self.entries.len());
+        for entry in &self.entries[self.revealed.min(events)..events] {
+            self.lines.extend(entry.iter().cloned());
+        }
+        self.revealed = self.revealed.max(events);
+    }
+
+    pub fn finish(&mut self, outcome: Outcome) {
+        if !self.finished {
+            self.reveal(self.entries.len());
+            self.lines.push(outcome.to_string());
+            self.finished = true;
+        }
+    }
+
+    // combat was called off before the log ran out
+    pub fn cancel(&mut self) {
+        if !self.finished && !self.lines.is_empty() {
+            self.lines.push("Combat cancelled".to_string());
+            self.finished = true;
+        }
+    }
+
+    pub fn text(&self) -> String {
+        self.lines.join("\n")
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod export {
+    use std::{fs, path::PathBuf};
+
+    use super::LOG_FILE;
+    use crate::save::data_dir;
+
+    pub fn copy(text: String) -> Result<String, String> {
+        let mut clipboard = arboard::Clipboard::new().map_err(|e| e.to_string())?;
+        clipboard.set_text(text).map_err(|e| e.to_string())?;
+        Ok("copied to clipboard".to_string())
+    }
+
+    pub fn save(text: String) -> Result<String, String> {
+        let dir: PathBuf = data_dir().ok_or("no data directory on this platform")?;
+        fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+        let path = dir.join(LOG_FILE);
+        fs::write(&path, text).map_err(|e| e.to_string())?;
+        Ok(format!("saved to {}", path.display()))
+    }
+}
+
+// the browser won't let us touch the clipboard or disk, so the console has to do
+#[cfg(target_arch = "wasm32")]
+mod export {
+    use bevy::prelude::info;
+
+    pub fn copy(text: String) -> Result<String, String> {
+        info!("combat log:\n{}", text);
+        Ok("written to the browser console".to_string())
+    }
+
+    pub fn save(text: String) -> Result<String, String> {
+        copy(text)
+    }
+}
+
+#[derive(Component, Default)]
+struct CombatLogPanel {
+    // lines scrolled up from the bottom
+    scroll: usize,
+    status: String,
+}
+
+#[derive(Component)]
+struct CombatLogText;
+
+fn setup_combat_log_panel(mut commands: Commands, text_details: Res<TextDetails>) {
+    let text_style = TextStyle {
+        font_size: 16.,
+        ..text_details.text_style.clone().unwrap()
+    };
+
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Px(520.0), Val::Px(320.0)),
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    right: Val::Px(10.0),
+                    top: Val::Px(10.0),
+                    ..default()
+                },
+                padding: UiRect::all(Val::Px(8.0)),
+                overflow: Overflow::Hidden,
+                ..default()
+            },
+            color: Color::rgba(1.0, 1.0, 1.0, 0.7).into(),
+            visibility: Visibility { is_visible: false },
+            ..default()
+        })
+        .insert(CombatLogPanel::default())
+        .insert(Interaction::default())
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(TextBundle::from_section("", text_style))
+                .insert(CombatLogText);
+        });
+}
+
+fn scroll_combat_log(
+    mut mouse_wheel_reader: EventReader<MouseWheel>,
+    mut panel_query: Query<(&Interaction, &mut CombatLogPanel)>,
+    combat_log: Res<CombatLog>,
+) {
+    let (interaction, mut panel) = panel_query.single_mut();
+    for ev in mouse_wheel_reader.iter() {
+        if *interaction == Interaction::None {
+            continue;
+        }
+        let max_scroll = combat_log.lines.len().saturating_sub(VISIBLE_LINES);
+        panel.scroll = if ev.y > 0.0 {
+            (panel.scroll + 1).min(max_scroll)
+        } else {
+            panel.scroll.saturating_sub(1)
+        };
+    }
+}
+
+fn export_combat_log(
+    input: Res<Input<KeyCode>>,
+    combat_log: Res<CombatLog>,
+    mut panel_query: Query<&mut CombatLogPanel>,
+) {
+    let ctrl = input.any_pressed([KeyCode::LControl, KeyCode::RControl]);
+    if !ctrl || combat_log.lines.is_empty() {
+        return;
+    }
+
+    let result = if input.just_pressed(KeyCode::C) {
+        export::copy(combat_log.text())
+    } else if input.just_pressed(KeyCode::S) {
+        export::save(combat_log.text())
+    } else {
+        return;
+    };
+
+    let mut panel = panel_query.single_mut();
+    panel.status = match result {
+        Ok(status) => format!("Log {}", status),
+        Err(e) => {
+            warn!("could not export the combat log: {}", e);
+            format!("Could not export the log: {}", e)
+        }
+    };
+}
+
+fn update_combat_log_panel(
+    combat_log: Res<CombatLog>,
+    panel_query: Query<(Entity, &CombatLogPanel, ChangeTrackers<CombatLogPanel>)>,
+    mut text_query: Query<&mut Text, With<CombatLogText>>,
+    mut visibility_query: Query<(&mut Visibility, Option<&Children>)>,
+) {
+    let (entity, panel, panel_tracker) = panel_query.single();
+    if !combat_log.is_changed() && !panel_tracker.is_changed() {
+        return;
+    }
+    set_panel_visible(entity, !combat_log.lines.is_empty(), &mut visibility_query);
+
+    let end = combat_log.lines.len().saturating_sub(panel.scroll);
+    let start = end.saturating_sub(VISIBLE_LINES);
+    let mut text = text_query.single_mut();
+    text.sections[0].value = format!(
+        "Combat log (ctrl+c to copy, ctrl+s to save)\n{}\n\n{}",
+        combat_log.lines[start..end].join("\n"),
+        panel.status,
+    );
+}
+
+pub struct CombatLogPlugin;
+impl Plugin for CombatLogPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(BasePlugin)
+            .add_startup_system(setup_combat_log_panel.after(setup_text_details))
+            .add_system(scroll_combat_log)
+            .add_system(export_combat_log)
+            .add_system(update_combat_log_panel);
+    }
+}
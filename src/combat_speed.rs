@@ -0,0 +1,125 @@
+use std::fmt;
+
+use bevy::prelude::*;
+
+use crate::{
+    combat::{Battle, Phase},
+    pause::unpaused,
+    smoothing, BasePlugin,
+};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TimeScale {
+    Normal,
+    Double,
+    Quadruple,
+}
+
+impl TimeScale {
+    fn factor(self) -> f32 {
+        match self {
+            TimeScale::Normal => 1.0,
+            TimeScale::Double => 2.0,
+            TimeScale::Quadruple => 4.0,
+        }
+    }
+
+    fn next(self) -> Self {
+        match self {
+            TimeScale::Normal => TimeScale::Double,
+            TimeScale::Double => TimeScale::Quadruple,
+            TimeScale::Quadruple => TimeScale::Normal,
+        }
+    }
+}
+
+// a resource, so the chosen speed carries over from one level to the next
+#[derive(Debug)]
+pub struct CombatSpeed {
+    pub time_scale: TimeScale,
+    // waits for a keypress before each exchange
+    pub stepping: bool,
+    queued_steps: usize,
+}
+
+impl Default for CombatSpeed {
+    fn default() -> Self {
+        CombatSpeed {
+            time_scale: TimeScale::Normal,
+            stepping: false,
+            queued_steps: 0,
+        }
+    }
+}
+
+impl CombatSpeed {
+    // `fraction` is how much of the remaining distance a lerp covers per frame at 1x,
+    // faster speeds play `delta` seconds as that many times as long
+    pub fn fraction(&self, fraction: f32, delta: f32) -> f32 {
+        smoothing(fraction, delta * self.time_scale.factor())
+    }
+
+    // whether the next exchange may start, using up a queued keypress in step mode
+    pub fn take_step(&mut self) -> bool {
+        if !self.stepping {
+            return true;
+        }
+        if self.queued_steps == 0 {
+            return false;
+        }
+        self.queued_steps -= 1;
+        true
+    }
+
+    pub fn clear_steps(&mut self) {
+        self.queued_steps = 0;
+    }
+}
+
+impl fmt::Display for CombatSpeed {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}x", self.time_scale.factor())?;
+        if self.stepping {
+            write!(f, ", step mode")?;
+        }
+        Ok(())
+    }
+}
+
+fn combat_speed_controls(
+    input: Res<Input<KeyCode>>,
+    mut speed: ResMut<CombatSpeed>,
+    mut battle: ResMut<Battle>,
+    phase: Res<State<Phase>>,
+) {
+    if input.just_pressed(KeyCode::F) {
+        speed.time_scale = speed.time_scale.next();
+    }
+
+    if input.just_pressed(KeyCode::T) {
+        speed.stepping = !speed.stepping;
+        speed.clear_steps();
+    }
+
+    if *phase.current() != Phase::COMBAT {
+        return;
+    }
+
+    if input.just_pressed(KeyCode::Right) && speed.stepping {
+        speed.queued_steps += 1;
+    }
+
+    // ctrl+s saves the combat log instead
+    let ctrl = input.any_pressed([KeyCode::LControl, KeyCode::RControl]);
+    if input.just_pressed(KeyCode::S) && !ctrl {
+        battle.skip = true;
+    }
+}
+
+pub struct CombatSpeedPlugin;
+impl Plugin for CombatSpeedPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(BasePlugin)
+            .add_system(combat_speed_controls.with_run_criteria(unpaused));
+    }
+}
@@ -1,5 +1,8 @@
 use bevy::prelude::*;
 
+use crate::BasePlugin;
+
+#[derive(Default)]
 pub struct Cursor(pub Vec2);
 
 fn update_cursor(
@@ -34,7 +37,6 @@ fn update_cursor(
 pub struct CursorPlugin;
 impl Plugin for CursorPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(Cursor(Vec2::new(0., 0.)))
-            .add_system(update_cursor);
+        app.add_plugin(BasePlugin).add_system(update_cursor);
     }
 }
@@ -0,0 +1,254 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy_easings::{
+    custom_ease_system, CustomComponentEase, Ease, EaseFunction, EasingType, EasingsPlugin, Lerp,
+};
+use bevy_prototype_lyon::prelude::*;
+use rand::Rng;
+
+use crate::{hud::TextDetails, orb::Stat, BasePlugin};
+
+const BURST_SHARDS: usize = 8;
+
+// put on an orb the frame it takes damage, the effects pick it up from there
+#[derive(Component)]
+pub struct Hit {
+    pub base_color: Color,
+}
+
+// effects that only live for as long as their animation
+#[derive(Component)]
+struct Lifetime(Timer);
+
+#[derive(Component, Default, Clone, Copy)]
+struct FloatingNumber {
+    origin: Vec3,
+    rise: f32,
+    alpha: f32,
+}
+
+impl Lerp for FloatingNumber {
+    type Scalar = f32;
+
+    fn lerp(&self, other: &Self, scalar: &Self::Scalar) -> Self {
+        FloatingNumber {
+            origin: self.origin,
+            rise: self.rise.lerp(&other.rise, scalar),
+            alpha: self.alpha.lerp(&other.alpha, scalar),
+        }
+    }
+}
+
+// 1 right after the hit, squashed and flashed white, easing back to 0
+#[derive(Component, Default, Clone, Copy)]
+struct HitReaction {
+    strength: f32,
+    base_color: Color,
+}
+
+impl Lerp for HitReaction {
+    type Scalar = f32;
+
+    fn lerp(&self, other: &Self, scalar: &Self::Scalar) -> Self {
+        HitReaction {
+            strength: self.strength.lerp(&other.strength, scalar),
+            base_color: self.base_color,
+        }
+    }
+}
+
+fn react_to_hits(mut commands: Commands, hit_query: Query<(Entity, &Hit)>) {
+    for (entity, hit) in hit_query.iter() {
+        commands.entity(entity).remove::<Hit>();
+
+        let reaction = HitReaction {
+            strength: 1.0,
+            base_color: hit.base_color,
+        };
+        insert_if_alive(
+            &mut commands,
+            entity,
+            reaction.ease_to(
+                HitReaction {
+                    strength: 0.0,
+                    ..reaction
+                },
+                EaseFunction::QuadraticOut,
+                EasingType::Once {
+                    duration: Duration::from_millis(300),
+                },
+            ),
+        );
+    }
+}
+
+// spawned where the hit lands rather than from `Hit`, a fatal hit's orb is gone by then
+pub fn spawn_damage_number(
+    commands: &mut Commands,
+    text_details: &TextDetails,
+    position: Vec3,
+    damage: Stat,
+) {
+    if damage <= Stat(0) {
+        return;
+    }
+
+    spawn_floating_text(
+        commands,
+        text_details,
+        position + Vec3::new(0., 40., 10.),
+        format!("-{}", damage),
+        32.,
+        Color::rgb(0.8, 0.1, 0.1),
+    );
+}
+
+// rises from `origin` and fades out
+pub fn spawn_floating_text(
+    commands: &mut Commands,
+    text_details: &TextDetails,
+    origin: Vec3,
+    value: String,
+    font_size: f32,
+    color: Color,
+) {
+    let number = FloatingNumber {
+        origin,
+        rise: 0.0,
+        alpha: 1.0,
+    };
+    commands
+        .spawn_bundle(Text2dBundle {
+            text: Text::from_section(
+                value,
+                TextStyle {
+                    font_size,
+                    color,
+                    ..text_details.text_style.clone().unwrap()
+                },
+            )
+            .with_alignment(text_details.text_alignment.unwrap()),
+            transform: Transform::from_translation(number.origin),
+            ..default()
+        })
+        .insert(Lifetime(Timer::from_seconds(0.8, false)))
+        .insert(number.ease_to(
+            FloatingNumber {
+                rise: 60.0,
+                alpha: 0.0,
+                ..number
+            },
+            EaseFunction::QuadraticOut,
+            EasingType::Once {
+                duration: Duration::from_millis(800),
+            },
+        ));
+}
+
+// the orb can be despawned before the insert is applied, e.g. when the level changes
+pub fn insert_if_alive(commands: &mut Commands, entity: Entity, component: impl Component) {
+    commands.add(move |world: &mut World| {
+        if let Some(mut entity) = world.get_entity_mut(entity) {
+            entity.insert(component);
+        }
+    });
+}
+
+fn float_numbers(
+    mut number_query: Query<(&FloatingNumber, &mut Transform, &mut Text), Changed<FloatingNumber>>,
+) {
+    for (number, mut t, mut text) in number_query.iter_mut() {
+        t.translation = number.origin + Vec3::new(0., number.rise, 0.);
+        for section in text.sections.iter_mut() {
+            section.style.color.set_a(number.alpha);
+        }
+    }
+}
+
+fn squash_and_flash(
+    mut reaction_query: Query<(&HitReaction, &mut Transform, &mut DrawMode), Changed<HitReaction>>,
+) {
+    for (reaction, mut t, mut d) in reaction_query.iter_mut() {
+        let s = reaction.strength;
+        t.scale = Vec3::new(1.0 + 0.25 * s, 1.0 - 0.25 * s, 1.0);
+
+        if let DrawMode::Outlined {
+            ref mut fill_mode,
+            outline_mode: _,
+        } = *d
+        {
+            let base = reaction.base_color;
+            fill_mode.color = Color::rgb(
+                base.r().lerp(&1.0, &s),
+                base.g().lerp(&1.0, &s),
+                base.b().lerp(&1.0, &s),
+            );
+        }
+    }
+}
+
+/// Scatters a ring of shards from where an orb just died.
+pub fn spawn_death_burst(commands: &mut Commands, position: Vec3, color: Color) {
+    let mut rng = rand::thread_rng();
+    for i in 0..BURST_SHARDS {
+        let angle =
+            i as f32 / BURST_SHARDS as f32 * std::f32::consts::TAU + rng.gen_range(-0.3..0.3);
+        let distance = rng.gen_range(60.0..110.0);
+        let start = Transform::from_translation(position + Vec3::Z * 5.)
+            .with_rotation(Quat::from_rotation_z(angle));
+        let end = Transform {
+            translation: start.translation + Vec3::new(angle.cos(), angle.sin(), 0.) * distance,
+            scale: Vec3::splat(0.1),
+            ..start
+        };
+
+        commands
+            .spawn_bundle(GeometryBuilder::build_as(
+                &shapes::RegularPolygon {
+                    sides: 3,
+                    feature: shapes::RegularPolygonFeature::Radius(12.),
+                    ..default()
+                },
+                DrawMode::Outlined {
+                    fill_mode: FillMode::color(color),
+                    outline_mode: StrokeMode::new(Color::BLACK, 2.0),
+                },
+                start,
+            ))
+            .insert(Lifetime(Timer::from_seconds(0.5, false)))
+            .insert(start.ease_to(
+                end,
+                EaseFunction::QuadraticOut,
+                EasingType::Once {
+                    duration: Duration::from_millis(500),
+                },
+            ));
+    }
+}
+
+fn expire_effects(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut lifetime_query: Query<(Entity, &mut Lifetime)>,
+) {
+    for (entity, mut lifetime) in lifetime_query.iter_mut() {
+        if lifetime.0.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+pub struct EffectsPlugin;
+impl Plugin for EffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(BasePlugin)
+            .add_plugin(EasingsPlugin)
+            .add_system(custom_ease_system::<FloatingNumber>)
+            .add_system(custom_ease_system::<HitReaction>)
+            .add_system(react_to_hits)
+            .add_system(float_numbers)
+            .add_system(squash_and_flash)
+            .add_system(expire_effects);
+    }
+}
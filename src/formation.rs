@@ -0,0 +1,129 @@
+use bevy::prelude::*;
+use bevy_prototype_lyon::prelude::*;
+
+use crate::{
+    combat::Phase,
+    effects::insert_if_alive,
+    hud::TextDetails,
+    orb::{update, ClosestCircle, PlayerOrb},
+    pause::on_update_unpaused,
+    smoothing, BasePlugin,
+};
+
+// how close an orb has to be dropped to a slot to take it
+const SNAP_RADIUS: f32 = 40.0;
+
+// slot 0 is the front of the line, right next to the enemies
+#[derive(Component)]
+pub struct FormationSlot(pub usize);
+
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct InSlot(pub usize);
+
+pub fn slot_position(index: usize) -> Vec3 {
+    Vec3::new(-150.0 - index as f32 * 80., -200.0, 0.0)
+}
+
+pub fn spawn_slots(commands: &mut Commands, count: usize, text_details: &TextDetails) {
+    for index in 0..count {
+        let slot = commands
+            .spawn_bundle(GeometryBuilder::build_as(
+                &shapes::Circle {
+                    center: Vec2::new(0., 0.),
+                    radius: 36.,
+                },
+                DrawMode::Stroke(StrokeMode::new(Color::rgba(0.4, 0.4, 0.7, 0.5), 3.0)),
+                Transform::from_translation(slot_position(index) - Vec3::Z),
+            ))
+            .insert(FormationSlot(index))
+            .id();
+
+        let number = commands
+            .spawn_bundle(Text2dBundle {
+                text: Text::from_section(
+                    format!("{}", index + 1),
+                    TextStyle {
+                        font_size: 22.,
+                        color: Color::rgba(0.2, 0.2, 0.4, 0.8),
+                        ..text_details.text_style.clone().unwrap()
+                    },
+                )
+                .with_alignment(text_details.text_alignment.unwrap()),
+                transform: Transform::from_translation(Vec3::new(0., -52., 0.)),
+                ..default()
+            })
+            .id();
+        commands.entity(slot).add_child(number);
+    }
+}
+
+// an orb resting close enough to a slot takes it and is pulled into place,
+// the closest orb wins when several are near the same slot
+fn assign_slots(
+    mut commands: Commands,
+    slot_query: Query<(&FormationSlot, &Transform), Without<PlayerOrb>>,
+    mut orb_query: Query<(Entity, &mut Transform, Option<&InSlot>), With<PlayerOrb>>,
+    closest_circle: Res<ClosestCircle>,
+    mouse_buttons: Res<Input<MouseButton>>,
+    time: Res<Time>,
+) {
+    let dragged = closest_circle
+        .data
+        .as_ref()
+        .filter(|_| mouse_buttons.pressed(MouseButton::Left))
+        .map(|data| data.entity);
+
+    let mut candidates: Vec<(f32, usize, Entity)> = vec![];
+    for (slot, slot_t) in slot_query.iter() {
+        for (entity, t, _) in orb_query.iter() {
+            let distance = t
+                .translation
+                .truncate()
+                .distance(slot_t.translation.truncate());
+            if distance < SNAP_RADIUS && Some(entity) != dragged {
+                candidates.push((distance, slot.0, entity));
+            }
+        }
+    }
+    candidates.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let mut assigned: Vec<(Entity, usize)> = vec![];
+    for (_, slot, entity) in candidates {
+        if assigned.iter().all(|&(e, s)| e != entity && s != slot) {
+            assigned.push((entity, slot));
+        }
+    }
+
+    let rate = smoothing(0.2, time.delta_seconds());
+    for (entity, mut t, in_slot) in orb_query.iter_mut() {
+        let slot = assigned
+            .iter()
+            .find(|(e, _)| *e == entity)
+            .map(|&(_, slot)| InSlot(slot));
+        if slot != in_slot.copied() {
+            match slot {
+                Some(slot) => insert_if_alive(&mut commands, entity, slot),
+                None => {
+                    commands.entity(entity).remove::<InSlot>();
+                }
+            }
+        }
+
+        if let Some(InSlot(index)) = slot {
+            let target = slot_position(index).truncate();
+            let pulled = t.translation.truncate().lerp(target, rate);
+            t.translation = pulled.extend(t.translation.z);
+        }
+    }
+}
+
+pub struct FormationPlugin;
+impl Plugin for FormationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(BasePlugin).add_system_set(
+            SystemSet::new()
+                .with_run_criteria(on_update_unpaused(Phase::PREP))
+                .with_system(assign_slots.after(update)),
+        );
+    }
+}
@@ -0,0 +1,168 @@
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{
+    level::{Level, LevelOrb},
+    orb::{MergeRule, Orb, Stat, StatCaps},
+    solver::{self, SolveReport},
+};
+
+pub const MAX_DIFFICULTY: u8 = 10;
+
+// candidates tried before settling for the closest match to the difficulty target
+const ATTEMPTS: usize = 64;
+
+/// Generates a level that is always winnable, as close to `difficulty`
+/// (1 to `MAX_DIFFICULTY`) as the seed allows.
+pub fn generate(seed: u64, difficulty: u8) -> Level {
+    let difficulty = difficulty.clamp(1, MAX_DIFFICULTY);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let mut best: Option<(f32, Vec<Orb>, Vec<Orb>)> = None;
+    let mut last = (vec![], vec![]);
+    for _ in 0..ATTEMPTS {
+        let player_orbs = random_pool(&mut rng, difficulty);
+        let enemy_orbs = random_enemies(&mut rng, difficulty, &player_orbs);
+
+        let report = solve(&player_orbs, &enemy_orbs);
+        if report.winnable() {
+            let distance = (score(&report) - difficulty as f32).abs();
+            if !matches!(&best, Some((best_distance, _, _)) if *best_distance <= distance) {
+                best = Some((distance, player_orbs.clone(), enemy_orbs.clone()));
+            }
+            if distance < 0.5 {
+                break;
+            }
+        }
+        last = (player_orbs, enemy_orbs);
+    }
+
+    let (player_orbs, enemy_orbs) = match best {
+        Some((_, player_orbs, enemy_orbs)) => (player_orbs, enemy_orbs),
+        None => {
+            let (player_orbs, enemy_orbs) = last;
+            let enemy_orbs = weaken_until_winnable(&player_orbs, enemy_orbs);
+            (player_orbs, enemy_orbs)
+        }
+    };
+
+    Level {
+        name: format!("Seed {}", seed),
+        description: format!("Difficulty {}/{}", difficulty, MAX_DIFFICULTY),
+        player_orbs: player_orbs.into_iter().map(LevelOrb::from).collect(),
+        enemy_orbs: enemy_orbs.into_iter().map(LevelOrb::from).collect(),
+        merge_rule: MergeRule::Additive,
+        stat_caps: StatCaps::default(),
+    }
+}
+
+// 0 when every line-up wins, up to MAX_DIFFICULTY when hardly any do
+fn score(report: &SolveReport) -> f32 {
+    let winning = report.solutions as f32 / report.line_ups_checked.max(1) as f32;
+    (1.0 - winning) * MAX_DIFFICULTY as f32
+}
+
+fn random_pool(rng: &mut StdRng, difficulty: u8) -> Vec<Orb> {
+    let count = 2 + difficulty / 3;
+    let max_stat = 3 + i32::from(difficulty) / 3;
+
+    let mut pool: Vec<Orb> = (0..count)
+        .map(|_| Orb {
+            damage: Stat(rng.gen_range(1..=max_stat)),
+            health: Stat(rng.gen_range(1..=max_stat)),
+            ..Orb::default()
+        })
+        .collect();
+
+    // harder levels sometimes hand out a cursed orb, strong but already dying
+    if difficulty >= 6 && rng.gen_bool(0.3) {
+        let cursed = rng.gen_range(0..pool.len());
+        pool[cursed].damage += Stat(2);
+        pool[cursed].health = Stat(-rng.gen_range(1..=3));
+    }
+
+    pool
+}
+
+fn random_enemies(rng: &mut StdRng, difficulty: u8, player_orbs: &[Orb]) -> Vec<Orb> {
+    let count = 1 + difficulty / 4;
+    let total_damage: Stat = player_orbs.iter().map(|orb| orb.damage).sum();
+    let total_health: Stat = player_orbs.iter().map(|orb| orb.health.max(Stat(1))).sum();
+    let per_enemy = |total: Stat| (total.0 / i32::from(count)).max(1);
+
+    (0..count)
+        .map(|_| Orb {
+            damage: Stat(rng.gen_range(1..=per_enemy(total_health))),
+            health: Stat(rng.gen_range(1..=per_enemy(total_damage))),
+            ..Orb::default()
+        })
+        .collect()
+}
+
+fn solve(player_orbs: &[Orb], enemy_orbs: &[Orb]) -> SolveReport {
+    solver::solve(
+        player_orbs,
+        enemy_orbs,
+        MergeRule::Additive,
+        StatCaps::default(),
+    )
+    .expect("generated pools are a handful of unmerged orbs")
+}
+
+// enemies that can't hurt and die in one hit always lose, so this terminates
+fn weaken_until_winnable(player_orbs: &[Orb], mut enemy_orbs: Vec<Orb>) -> Vec<Orb> {
+    while !solve(player_orbs, &enemy_orbs).winnable() {
+        for orb in &mut enemy_orbs {
+            orb.damage = Stat(orb.damage.0 / 2);
+            orb.health = Stat((orb.health.0 / 2).max(1));
+        }
+    }
+    enemy_orbs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEEDS: [u64; 3] = [0, 42, 2022];
+
+    fn orbs(level_orbs: &[LevelOrb]) -> Vec<Orb> {
+        level_orbs.iter().map(|o| o.orb.clone()).collect()
+    }
+
+    fn stats(level_orbs: &[LevelOrb]) -> Vec<(Stat, Stat)> {
+        level_orbs
+            .iter()
+            .map(|o| (o.orb.damage, o.orb.health))
+            .collect()
+    }
+
+    #[test]
+    fn generated_levels_are_always_winnable() {
+        for seed in SEEDS {
+            for difficulty in 1..=MAX_DIFFICULTY {
+                let level = generate(seed, difficulty);
+                let report = solver::solve(
+                    &orbs(&level.player_orbs),
+                    &orbs(&level.enemy_orbs),
+                    level.merge_rule,
+                    level.stat_caps,
+                )
+                .unwrap();
+                assert!(report.winnable(), "seed {} difficulty {}", seed, difficulty);
+            }
+        }
+    }
+
+    #[test]
+    fn the_same_seed_and_difficulty_give_the_same_level() {
+        for seed in SEEDS {
+            for difficulty in 1..=MAX_DIFFICULTY {
+                let (a, b) = (generate(seed, difficulty), generate(seed, difficulty));
+                assert_eq!(a.name, b.name);
+                assert_eq!(a.description, b.description);
+                assert_eq!(stats(&a.player_orbs), stats(&b.player_orbs));
+                assert_eq!(stats(&a.enemy_orbs), stats(&b.enemy_orbs));
+            }
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use bevy::prelude::*;
+
+use crate::{
+    combat::Phase,
+    cursor::Cursor,
+    hud::TextDetails,
+    orb::{spawn_orb, update, ClosestCircle, Orb, PlayerOrb},
+    orb_animation::Animating,
+    pause::on_update_unpaused,
+    BasePlugin,
+};
+
+// how far the cursor has to travel before a drag counts as a reposition
+const DRAG_THRESHOLD: f32 = 5.0;
+
+pub type Snapshot = Vec<(Orb, Vec3)>;
+
+#[derive(Default)]
+pub struct PrepHistory {
+    undo: Vec<Snapshot>,
+    redo: Vec<Snapshot>,
+    // taken when a drag starts, only kept if the drag ends up changing something
+    pending: Option<(Snapshot, Vec2)>,
+}
+
+impl PrepHistory {
+    pub fn clear(&mut self) {
+        *self = PrepHistory::default();
+    }
+
+    pub fn commit(&mut self, snapshot: Snapshot) {
+        self.undo.push(snapshot);
+        self.redo.clear();
+    }
+}
+
+pub fn snapshot(player_orb_query: &Query<(Entity, &Orb, &Transform), With<PlayerOrb>>) -> Snapshot {
+    player_orb_query
+        .iter()
+        .map(|(_, orb, t)| (orb.clone(), t.translation))
+        .collect()
+}
+
+// runs before `update` so the snapshots see the orbs as they were before the action
+fn record_prep_actions(
+    mut history: ResMut<PrepHistory>,
+    player_orb_query: Query<(Entity, &Orb, &Transform), With<PlayerOrb>>,
+    mouse_buttons: Res<Input<MouseButton>>,
+    cursor: Res<Cursor>,
+    closest_circle: Res<ClosestCircle>,
+) {
+    if let Some(closest) = &closest_circle.data {
+        if mouse_buttons.just_pressed(MouseButton::Left) {
+            history.pending = Some((snapshot(&player_orb_query), cursor.0));
+        }
+        if mouse_buttons.just_pressed(MouseButton::Right) && !closest.orb.children.is_empty() {
+            history.commit(snapshot(&player_orb_query));
+        }
+    }
+
+    if mouse_buttons.just_released(MouseButton::Left) {
+        if let Some((snapshot, origin)) = history.pending.take() {
+            if origin.distance(cursor.0) > DRAG_THRESHOLD {
+                history.commit(snapshot);
+            }
+        }
+    }
+}
+
+fn drop_pending(mut history: ResMut<PrepHistory>) {
+    history.pending = None;
+}
+
+fn undo_redo(
+    mut commands: Commands,
+    input: Res<Input<KeyCode>>,
+    mut history: ResMut<PrepHistory>,
+    player_orb_query: Query<(Entity, &Orb, &Transform), With<PlayerOrb>>,
+    mut closest_circle: ResMut<ClosestCircle>,
+    text_details: Res<TextDetails>,
+    animating_query: Query<(), With<Animating>>,
+) {
+    let ctrl = input.pressed(KeyCode::LControl) || input.pressed(KeyCode::RControl);
+    // a merge or split is only part of the board once its animation is done
+    if !ctrl || !input.just_pressed(KeyCode::Z) || !animating_query.is_empty() {
+        return;
+    }
+    let shift = input.pressed(KeyCode::LShift) || input.pressed(KeyCode::RShift);
+
+    let current = snapshot(&player_orb_query);
+    let restored = if shift {
+        let restored = history.redo.pop();
+        if restored.is_some() {
+            history.undo.push(current);
+        }
+        restored
+    } else {
+        let restored = history.undo.pop();
+        if restored.is_some() {
+            history.redo.push(current);
+        }
+        restored
+    };
+
+    if let Some(restored) = restored {
+        for (entity, _, _) in player_orb_query.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+        for (orb, position) in restored.iter() {
+            spawn_orb(&mut commands, *position, orb, text_details.clone(), false);
+        }
+        history.pending = None;
+        closest_circle.data = None;
+    }
+}
+
+pub struct HistoryPlugin;
+impl Plugin for HistoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(BasePlugin)
+            .add_system_set(
+                SystemSet::new()
+                    .with_run_criteria(on_update_unpaused(Phase::PREP))
+                    .with_system(record_prep_actions.before(update))
+                    .with_system(undo_redo),
+            )
+            .add_system_set(SystemSet::on_exit(Phase::PREP).with_system(drop_pending));
+    }
+}
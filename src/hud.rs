@@ -0,0 +1,372 @@
+use bevy::prelude::*;
+use bevy_easings::Lerp;
+
+use crate::{
+    combat::Phase,
+    combat_speed::CombatSpeed,
+    level::{CampaignLevels, ChangeLevel, CurrentLevel, Level},
+    level_select::OpenLevelSelect,
+    orb::{MergeRule, StatCaps},
+    pause::unpaused,
+    save::SaveData,
+    smoothing, BasePlugin,
+};
+
+const CONTROLS: &str = "Welcome to Orber! \n\nControls: \n - press SPACE to initiate combat \n - drag orbs over each-other to combine \n - drag orbs into the numbered slots to set the attack order \n - right click an orb to split it, 1-9 while hovering to detach one piece, X to shatter it \n - hover an orb (or hold I) to see what it is made of \n - ctrl+z to undo, ctrl+shift+z to redo \n - R to restart the level \n - H to hide the outcome prediction (hard mode) \n - F to change combat speed, T for step mode (RIGHT to step), S to skip \n - ESC to pause";
+
+#[derive(Clone)]
+pub struct HudSettings {
+    pub font_path: String,
+    pub clear_color: Color,
+    // the controls list under the level details
+    pub show_controls: bool,
+    pub combat_zoom: f32,
+}
+
+impl Default for HudSettings {
+    fn default() -> Self {
+        HudSettings {
+            font_path: "fonts/FiraMono-Medium.ttf".to_string(),
+            clear_color: Color::rgb(0.9, 0.9, 1.0),
+            show_controls: true,
+            combat_zoom: 0.6,
+        }
+    }
+}
+
+fn setup_camera(mut commands: Commands) {
+    commands.spawn_bundle(Camera2dBundle {
+        projection: OrthographicProjection::default(),
+        ..default()
+    });
+}
+
+// filled in by `setup_text_details` once the font starts loading
+#[derive(Clone, Default)]
+pub struct TextDetails {
+    pub text_style: Option<TextStyle>,
+    pub text_alignment: Option<TextAlignment>,
+}
+
+fn setup_ui(mut commands: Commands, text_details: Res<TextDetails>) {
+    let existing_style = text_details.text_style.clone().unwrap();
+    commands
+        .spawn_bundle(
+            TextBundle::from_sections([
+                TextSection::new(
+                    "\n",
+                    TextStyle {
+                        font: existing_style.font.clone(),
+                        font_size: 30.,
+                        color: Color::BLACK,
+                    },
+                ),
+                TextSection::new(
+                    CONTROLS,
+                    TextStyle {
+                        font: existing_style.font.clone(),
+                        font_size: 30.,
+                        color: Color::BLACK,
+                    },
+                ),
+            ])
+            .with_style(Style {
+                margin: UiRect::all(Val::Px(10.0)),
+                ..default()
+            }),
+        )
+        .insert(UiText);
+}
+
+fn update_level_display(
+    mut ui_text_query: Query<&mut Text, With<UiText>>,
+    current_level: Res<CurrentLevel>,
+    campaign_levels: Res<CampaignLevels>,
+    levels: Res<Assets<Level>>,
+    speed: Res<CombatSpeed>,
+    settings: Res<HudSettings>,
+) {
+    let mut text = ui_text_query.single_mut();
+    if campaign_levels.is_complete(current_level.0) {
+        text.sections[0].value = format!("Game complete! Well done!! | you can press SPACE to restart, or E for endless levels :)\n\n");
+        text.sections[1].value = format!("Enjoy the rest of the competition. \nThis game was made in a few days using the Bevy game engine.\n\n Good luck to all the other contestants! <3 \n -jake");
+    } else {
+        let level_details = campaign_levels
+            .get(current_level.0, &levels)
+            .map(|level| {
+                let mut details = format!("- {} \n{}", level.name, level.description);
+                if level.merge_rule != MergeRule::Additive {
+                    details += &format!("\nMerge rule: {}", level.merge_rule);
+                }
+                if level.stat_caps != StatCaps::default() {
+                    details += &format!(
+                        "\nStat caps: {}/{}",
+                        level.stat_caps.damage, level.stat_caps.health
+                    );
+                }
+                details
+            })
+            .unwrap_or_default();
+        text.sections[0].value = format!(
+            "Current Level: {} {}\nCombat speed: {}\n\n",
+            current_level.0 + 1,
+            level_details,
+            *speed
+        );
+        text.sections[1].value = if settings.show_controls {
+            CONTROLS.to_string()
+        } else {
+            String::new()
+        };
+    }
+}
+
+#[derive(Component)]
+struct UiText;
+
+pub fn setup_text_details(
+    mut text_details: ResMut<TextDetails>,
+    asset_server: Res<AssetServer>,
+    settings: Res<HudSettings>,
+) {
+    let text_alignment = TextAlignment::CENTER;
+
+    text_details.text_style = Some(TextStyle {
+        font: asset_server.load(settings.font_path.as_str()),
+        font_size: 20.0,
+        color: Color::BLACK,
+    });
+    text_details.text_alignment = Some(text_alignment);
+}
+
+// levels are loaded asynchronously, so wait for them before spawning the first one
+fn start_campaign(
+    mut commands: Commands,
+    mut change_level_writer: EventWriter<ChangeLevel>,
+    campaign_levels: Res<CampaignLevels>,
+    asset_server: Res<AssetServer>,
+    save: Res<SaveData>,
+    text_details: Res<TextDetails>,
+    mut started: Local<bool>,
+) {
+    if !*started && campaign_levels.is_loaded(&asset_server) {
+        if save.current_level > 0 {
+            spawn_start_menu(&mut commands, &text_details, save.current_level);
+        } else {
+            change_level_writer.send(ChangeLevel::Goto(0));
+        }
+        *started = true;
+    }
+}
+
+#[derive(Component)]
+pub struct StartMenu;
+
+#[derive(Component, Clone, Copy)]
+enum StartMenuButton {
+    Continue,
+    NewGame,
+    LevelSelect,
+    // on the prompt shown before a new game wipes the save
+    ConfirmNewGame,
+    Back,
+}
+
+fn start_menu_node() -> NodeBundle {
+    NodeBundle {
+        style: Style {
+            size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+            position_type: PositionType::Absolute,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            flex_direction: FlexDirection::ColumnReverse,
+            ..default()
+        },
+        color: Color::NONE.into(),
+        ..default()
+    }
+}
+
+pub fn spawn_start_menu(commands: &mut Commands, text_details: &TextDetails, saved_level: usize) {
+    let text_style = TextStyle {
+        font_size: 30.,
+        ..text_details.text_style.clone().unwrap()
+    };
+
+    commands
+        .spawn_bundle(start_menu_node())
+        .insert(StartMenu)
+        .with_children(|parent| {
+            spawn_menu_button(
+                parent,
+                StartMenuButton::Continue,
+                format!("Continue (level {})", saved_level + 1),
+                text_style.clone(),
+            );
+            spawn_menu_button(
+                parent,
+                StartMenuButton::NewGame,
+                "New game",
+                text_style.clone(),
+            );
+            spawn_menu_button(
+                parent,
+                StartMenuButton::LevelSelect,
+                "Level select",
+                text_style,
+            );
+        });
+}
+
+fn spawn_new_game_prompt(commands: &mut Commands, text_details: &TextDetails) {
+    let text_style = TextStyle {
+        font_size: 30.,
+        ..text_details.text_style.clone().unwrap()
+    };
+
+    commands
+        .spawn_bundle(start_menu_node())
+        .insert(StartMenu)
+        .with_children(|parent| {
+            parent.spawn_bundle(TextBundle::from_section(
+                "Start over? Your progress and best results will be lost.",
+                text_style.clone(),
+            ));
+            spawn_menu_button(
+                parent,
+                StartMenuButton::ConfirmNewGame,
+                "Start over",
+                text_style.clone(),
+            );
+            spawn_menu_button(parent, StartMenuButton::Back, "Back", text_style);
+        });
+}
+
+// UI visibility isn't inherited, so the panel's text is shown and hidden along with it
+pub fn set_panel_visible(
+    panel: Entity,
+    visible: bool,
+    visibility_query: &mut Query<(&mut Visibility, Option<&Children>)>,
+) {
+    let children = match visibility_query.get_mut(panel) {
+        Ok((mut visibility, children)) => {
+            visibility.is_visible = visible;
+            children
+                .map(|children| children.to_vec())
+                .unwrap_or_default()
+        }
+        Err(_) => return,
+    };
+    for child in children {
+        set_panel_visible(child, visible, visibility_query);
+    }
+}
+
+pub fn spawn_menu_button(
+    parent: &mut ChildBuilder,
+    button: impl Component,
+    label: impl Into<String>,
+    text_style: TextStyle,
+) {
+    parent
+        .spawn_bundle(ButtonBundle {
+            style: Style {
+                size: Size::new(Val::Px(360.0), Val::Px(65.0)),
+                margin: UiRect::all(Val::Px(10.0)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            color: Color::rgb(0.4, 0.4, 0.7).into(),
+            ..default()
+        })
+        .insert(button)
+        .with_children(|parent| {
+            parent.spawn_bundle(TextBundle::from_section(label, text_style));
+        });
+}
+
+fn start_menu_buttons(
+    mut commands: Commands,
+    interaction_query: Query<(&Interaction, &StartMenuButton), Changed<Interaction>>,
+    start_menu_query: Query<Entity, With<StartMenu>>,
+    mut change_level_writer: EventWriter<ChangeLevel>,
+    mut open_level_select_writer: EventWriter<OpenLevelSelect>,
+    mut save: ResMut<SaveData>,
+    text_details: Res<TextDetails>,
+) {
+    for (interaction, button) in interaction_query.iter() {
+        if *interaction != Interaction::Clicked {
+            continue;
+        }
+
+        match button {
+            StartMenuButton::Continue => {
+                change_level_writer.send(ChangeLevel::Goto(save.current_level))
+            }
+            StartMenuButton::NewGame => spawn_new_game_prompt(&mut commands, &text_details),
+            StartMenuButton::LevelSelect => open_level_select_writer.send(OpenLevelSelect),
+            StartMenuButton::ConfirmNewGame => {
+                *save = SaveData::default();
+                change_level_writer.send(ChangeLevel::Goto(0));
+            }
+            StartMenuButton::Back => {
+                spawn_start_menu(&mut commands, &text_details, save.current_level)
+            }
+        }
+
+        for menu in start_menu_query.iter() {
+            commands.entity(menu).despawn_recursive();
+        }
+    }
+}
+
+fn modify_camera_scale(
+    phase: Res<State<Phase>>,
+    mut camera: Query<&mut Transform, With<Camera>>,
+    windows: Res<Windows>,
+    time: Res<Time>,
+    settings: Res<HudSettings>,
+) {
+    let window = windows.get_primary().unwrap();
+    let (w, h) = (window.width(), window.height());
+    let default_w = 1280.0;
+    let default_h = 720.0;
+
+    let mut camera_transform = camera.single_mut();
+    let relative_to_default: Vec2 = Vec2::new(default_w / w, default_h / h);
+    let zoom_level = if *phase.current() == Phase::COMBAT {
+        settings.combat_zoom
+    } else {
+        1.0
+    };
+    let rate = smoothing(0.2, time.delta_seconds());
+
+    camera_transform.scale.x = camera_transform
+        .scale
+        .x
+        .lerp(&(relative_to_default.x * zoom_level), &rate);
+    camera_transform.scale.y = camera_transform
+        .scale
+        .y
+        .lerp(&(relative_to_default.y * zoom_level), &rate);
+}
+
+#[derive(Default)]
+pub struct HudPlugin {
+    pub settings: HudSettings,
+}
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(BasePlugin)
+            .insert_resource(ClearColor(self.settings.clear_color))
+            .insert_resource(self.settings.clone())
+            .add_startup_system(setup_camera)
+            .add_startup_system(setup_ui.after(setup_text_details))
+            .add_system(start_campaign)
+            .add_system(start_menu_buttons)
+            .add_system(modify_camera_scale.with_run_criteria(unpaused))
+            .add_system(update_level_display);
+    }
+}
@@ -0,0 +1,233 @@
+use bevy::prelude::*;
+
+use crate::{
+    combat::Phase,
+    cursor::Cursor,
+    history::{snapshot, PrepHistory},
+    hud::{set_panel_visible, setup_text_details, TextDetails},
+    orb::{update, ClosestCircle, EnemyOrb, MergeRule, Orb, OrbSplit, PlayerOrb},
+    orb_animation,
+    pause::on_update_unpaused,
+    BasePlugin,
+};
+
+const DETACH_KEYS: [KeyCode; 9] = [
+    KeyCode::Key1,
+    KeyCode::Key2,
+    KeyCode::Key3,
+    KeyCode::Key4,
+    KeyCode::Key5,
+    KeyCode::Key6,
+    KeyCode::Key7,
+    KeyCode::Key8,
+    KeyCode::Key9,
+];
+
+#[derive(Component)]
+struct Inspector;
+
+#[derive(Component)]
+struct InspectorText;
+
+fn setup_inspector(mut commands: Commands, text_details: Res<TextDetails>) {
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    left: Val::Px(10.0),
+                    top: Val::Px(320.0),
+                    ..default()
+                },
+                padding: UiRect::all(Val::Px(8.0)),
+                ..default()
+            },
+            color: Color::rgba(1.0, 1.0, 1.0, 0.8).into(),
+            visibility: Visibility { is_visible: false },
+            ..default()
+        })
+        .insert(Inspector)
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(TextBundle {
+                    text: Text::from_section(
+                        "",
+                        TextStyle {
+                            font_size: 18.,
+                            ..text_details.text_style.clone().unwrap()
+                        },
+                    ),
+                    visibility: Visibility { is_visible: false },
+                    ..default()
+                })
+                .insert(InspectorText);
+        });
+}
+
+// hovering a player orb in prep shows it, holding I shows any orb under the cursor
+fn inspected_orb(
+    input: &Input<KeyCode>,
+    cursor: &Cursor,
+    closest_circle: &ClosestCircle,
+    phase: &Phase,
+    orb_query: &Query<(&Transform, &Orb, Option<&EnemyOrb>)>,
+) -> Option<(Orb, bool)> {
+    if input.pressed(KeyCode::I) {
+        return orb_query
+            .iter()
+            .filter(|(t, _, enemy)| {
+                let radius = if enemy.is_some() { 50.0 } else { 30.0 };
+                t.translation.truncate().distance(cursor.0) < radius
+            })
+            .min_by(|a, b| {
+                let distance = |t: &Transform| t.translation.truncate().distance(cursor.0);
+                distance(a.0).total_cmp(&distance(b.0))
+            })
+            .map(|(_, orb, enemy)| (orb.clone(), enemy.is_some()));
+    }
+    if *phase != Phase::PREP {
+        return None;
+    }
+    closest_circle
+        .data
+        .as_ref()
+        .map(|data| (data.orb.clone(), false))
+}
+
+fn update_inspector(
+    input: Res<Input<KeyCode>>,
+    cursor: Res<Cursor>,
+    closest_circle: Res<ClosestCircle>,
+    phase: Res<State<Phase>>,
+    orb_query: Query<(&Transform, &Orb, Option<&EnemyOrb>)>,
+    mut text_query: Query<&mut Text, With<InspectorText>>,
+    inspector_query: Query<Entity, With<Inspector>>,
+    mut visibility_query: Query<(&mut Visibility, Option<&Children>)>,
+) {
+    let value = match inspected_orb(
+        &input,
+        &cursor,
+        &closest_circle,
+        phase.current(),
+        &orb_query,
+    ) {
+        Some((orb, is_enemy)) => {
+            let split = if orb.children.is_empty() {
+                "can't be split any further".to_string()
+            } else if is_enemy {
+                String::new()
+            } else {
+                let children: Vec<String> =
+                    orb.children.iter().map(|child| child.to_string()).collect();
+                format!(
+                    "right click splits it into {}\n1-9 detaches that piece only\nX shatters it into {}",
+                    children.join(" + "),
+                    orb.leaves().len(),
+                )
+            };
+            // numbered to match the detach keys
+            let tree: Vec<String> = orb
+                .tree()
+                .lines()
+                .enumerate()
+                .map(|(i, line)| match i {
+                    0 => format!("    {}", line),
+                    i if i <= DETACH_KEYS.len() && !is_enemy => format!("{:>2}. {}", i, line),
+                    _ => format!("    {}", line),
+                })
+                .collect();
+            format!(
+                "{} (damage/health)\n{}\n\n{}",
+                if is_enemy { "Enemy" } else { "Orb" },
+                tree.join("\n"),
+                split
+            )
+        }
+        None => String::new(),
+    };
+
+    let mut text = text_query.single_mut();
+    if text.sections[0].value == value {
+        return;
+    }
+    set_panel_visible(
+        inspector_query.single(),
+        !value.is_empty(),
+        &mut visibility_query,
+    );
+    text.sections[0].value = value;
+}
+
+// number keys pull a single piece out of the hovered orb, X breaks it down to its leaves
+fn detach_and_shatter(
+    mut commands: Commands,
+    input: Res<Input<KeyCode>>,
+    mut closest_circle: ResMut<ClosestCircle>,
+    mut history: ResMut<PrepHistory>,
+    player_orb_query: Query<(Entity, &Orb, &Transform), With<PlayerOrb>>,
+    text_details: Res<TextDetails>,
+    merge_rule: Res<MergeRule>,
+    mut split_writer: EventWriter<OrbSplit>,
+) {
+    let (entity, orb, t) = match closest_circle
+        .data
+        .as_ref()
+        .and_then(|data| player_orb_query.get(data.entity).ok())
+    {
+        Some(hovered) => hovered,
+        None => return,
+    };
+
+    let pieces = if input.just_pressed(KeyCode::X) {
+        orb.leaves()
+    } else {
+        let paths = orb.sub_orb_paths();
+        let detached = DETACH_KEYS
+            .iter()
+            .zip(paths.iter())
+            .find(|(key, _)| input.just_pressed(**key))
+            .and_then(|(_, path)| orb.detach(path, *merge_rule));
+        match detached {
+            Some((rest, detached)) => vec![rest, detached],
+            None => return,
+        }
+    };
+    if pieces.len() < 2 {
+        return;
+    }
+
+    history.commit(snapshot(&player_orb_query));
+    let others: Vec<Vec3> = player_orb_query
+        .iter()
+        .filter(|(other, _, _)| *other != entity)
+        .map(|(_, _, t)| t.translation)
+        .collect();
+    orb_animation::burst_split(
+        &mut commands,
+        t.translation,
+        &pieces,
+        &others,
+        text_details.clone(),
+    );
+    commands.entity(entity).despawn_recursive();
+    closest_circle.data = None;
+    split_writer.send(OrbSplit {
+        orb: orb.clone(),
+        pieces,
+        position: t.translation,
+    });
+}
+
+pub struct InspectorPlugin;
+impl Plugin for InspectorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(BasePlugin)
+            .add_startup_system(setup_inspector.after(setup_text_details))
+            .add_system(update_inspector)
+            .add_system_set(
+                SystemSet::new()
+                    .with_run_criteria(on_update_unpaused(Phase::PREP))
+                    .with_system(detach_and_shatter.before(update)),
+            );
+    }
+}
@@ -0,0 +1,315 @@
+use bevy::{
+    asset::{AssetLoader, HandleId, LoadContext, LoadState, LoadedAsset},
+    prelude::*,
+    reflect::TypeUuid,
+    utils::BoxedFuture,
+};
+use serde::Deserialize;
+
+use crate::{
+    combat::CombatSettings,
+    formation::{self, FormationSlot},
+    generator,
+    history::PrepHistory,
+    hud::TextDetails,
+    orb::{spawn_orb, MergeRule, Orb, StatCaps},
+    save::{LevelResult, SaveData},
+    BasePlugin,
+};
+
+pub const CAMPAIGN_PATH: &str = "levels/main.campaign.ron";
+
+#[derive(Clone)]
+pub struct LevelSettings {
+    // relative to the assets folder
+    pub campaign_path: String,
+}
+
+impl Default for LevelSettings {
+    fn default() -> Self {
+        LevelSettings {
+            campaign_path: CAMPAIGN_PATH.to_string(),
+        }
+    }
+}
+
+pub enum ChangeLevel {
+    Restart,
+    Next,
+    Goto(usize),
+}
+
+#[derive(Default)]
+pub struct CurrentLevel(pub usize);
+
+// sent when a battle is won, before the next level is loaded
+pub struct LevelCompleted {
+    pub level: usize,
+    pub result: LevelResult,
+}
+
+// once the campaign is beaten, levels past the last file are generated on the fly
+pub struct Endless {
+    pub enabled: bool,
+    pub seed: u64,
+    pub first_level: usize,
+}
+
+impl Default for Endless {
+    fn default() -> Self {
+        Endless {
+            enabled: false,
+            seed: rand::random(),
+            first_level: 0,
+        }
+    }
+}
+
+#[derive(Deserialize, TypeUuid, Debug, Clone)]
+#[uuid = "1f0e7a8c-3b5d-4c2e-9a61-7d4b2e8f5c90"]
+pub struct Level {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub player_orbs: Vec<LevelOrb>,
+    pub enemy_orbs: Vec<LevelOrb>,
+    #[serde(default)]
+    pub merge_rule: MergeRule,
+    #[serde(default)]
+    pub stat_caps: StatCaps,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct LevelOrb {
+    pub orb: Orb,
+    // falls back to the default line-up slot when missing
+    #[serde(default)]
+    pub position: Option<(f32, f32)>,
+}
+
+impl From<Orb> for LevelOrb {
+    fn from(orb: Orb) -> Self {
+        LevelOrb {
+            orb,
+            position: None,
+        }
+    }
+}
+
+impl LevelOrb {
+    pub fn position_or(&self, default: Vec3) -> Vec3 {
+        self.position
+            .map(|(x, y)| Vec3::new(x, y, 0.0))
+            .unwrap_or(default)
+    }
+}
+
+// ordered list of level files, relative to the assets folder
+#[derive(Deserialize, TypeUuid, Debug)]
+#[uuid = "8a2d4f6b-9c1e-4e7a-b3d5-0f6c8e2a4b17"]
+pub struct Campaign {
+    pub levels: Vec<String>,
+}
+
+#[derive(Default)]
+pub struct CampaignLevels {
+    campaign: Handle<Campaign>,
+    pub levels: Vec<Handle<Level>>,
+}
+
+impl CampaignLevels {
+    pub fn get<'a>(&self, index: usize, levels: &'a Assets<Level>) -> Option<&'a Level> {
+        self.levels.get(index).and_then(|handle| levels.get(handle))
+    }
+
+    pub fn is_loaded(&self, asset_server: &AssetServer) -> bool {
+        // generated levels never went through the asset server
+        let from_files = self
+            .levels
+            .iter()
+            .map(|h| h.id)
+            .filter(|id| matches!(id, HandleId::AssetPathId(_)));
+        !self.levels.is_empty()
+            && asset_server.get_group_load_state(from_files) == LoadState::Loaded
+    }
+
+    pub fn is_generated(&self, index: usize) -> bool {
+        self.levels
+            .get(index)
+            .is_some_and(|handle| !matches!(handle.id, HandleId::AssetPathId(_)))
+    }
+
+    // generated levels are played through the same handles as the ones loaded from files
+    pub fn push(&mut self, level: Level, levels: &mut Assets<Level>) {
+        self.levels.push(levels.add(level));
+    }
+
+    // the campaign is over once the current level points past the last level
+    pub fn is_complete(&self, current_level: usize) -> bool {
+        !self.levels.is_empty() && current_level >= self.levels.len()
+    }
+}
+
+#[derive(Default)]
+struct LevelLoader;
+
+impl AssetLoader for LevelLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let level = ron::de::from_bytes::<Level>(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(level));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["level.ron"]
+    }
+}
+
+#[derive(Default)]
+struct CampaignLoader;
+
+impl AssetLoader for CampaignLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let campaign = ron::de::from_bytes::<Campaign>(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(campaign));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["campaign.ron"]
+    }
+}
+
+fn load_campaign(
+    mut campaign_levels: ResMut<CampaignLevels>,
+    asset_server: Res<AssetServer>,
+    settings: Res<LevelSettings>,
+) {
+    campaign_levels.campaign = asset_server.load(settings.campaign_path.as_str());
+}
+
+fn load_campaign_levels(
+    mut campaign_levels: ResMut<CampaignLevels>,
+    campaigns: Res<Assets<Campaign>>,
+    asset_server: Res<AssetServer>,
+) {
+    if !campaign_levels.levels.is_empty() {
+        return;
+    }
+
+    if let Some(campaign) = campaigns.get(&campaign_levels.campaign) {
+        campaign_levels.levels = campaign
+            .levels
+            .iter()
+            .map(|path| asset_server.load(path.as_str()))
+            .collect();
+    }
+}
+
+fn change_level(
+    mut change_level_reader: EventReader<ChangeLevel>,
+    mut current_level: ResMut<CurrentLevel>,
+    mut commands: Commands,
+    // current entities
+    orb_query: Query<Entity, Or<(With<Orb>, With<FormationSlot>)>>,
+    text_details: Res<TextDetails>,
+    mut campaign_levels: ResMut<CampaignLevels>,
+    mut levels: ResMut<Assets<Level>>,
+    endless: Res<Endless>,
+    mut save: ResMut<SaveData>,
+    mut history: ResMut<PrepHistory>,
+    combat_settings: Res<CombatSettings>,
+) {
+    for ev in change_level_reader.iter() {
+        current_level.0 = match ev {
+            ChangeLevel::Restart => current_level.0,
+            ChangeLevel::Next => current_level.0 + 1,
+            ChangeLevel::Goto(index) => *index,
+        };
+        // despawn current
+        orb_query.iter().for_each(|e| {
+            commands.entity(e).despawn_recursive();
+        });
+        history.clear();
+
+        let index = current_level.0;
+        if endless.enabled && index >= campaign_levels.levels.len() {
+            let difficulty =
+                (index - endless.first_level + 1).min(generator::MAX_DIFFICULTY as usize);
+            let level =
+                generator::generate(endless.seed.wrapping_add(index as u64), difficulty as u8);
+            campaign_levels.push(level, &mut levels);
+        }
+
+        if !campaign_levels.is_generated(index) {
+            save.current_level = current_level.0;
+            save.highest_unlocked = save.highest_unlocked.max(current_level.0);
+        }
+
+        if let Some(level) = campaign_levels.get(index, &levels) {
+            commands.insert_resource(level.merge_rule);
+            commands.insert_resource(level.stat_caps);
+
+            // enough slots for every orb the pool can be split into
+            let slots = level
+                .player_orbs
+                .iter()
+                .map(|level_orb| level_orb.orb.leaf_count())
+                .sum();
+            formation::spawn_slots(&mut commands, slots, &text_details);
+
+            for (i, level_orb) in level.player_orbs.iter().enumerate() {
+                let position = level_orb.position_or(formation::slot_position(i));
+                spawn_orb(
+                    &mut commands,
+                    position,
+                    &level_orb.orb,
+                    text_details.clone(),
+                    false,
+                );
+            }
+
+            for (i, level_orb) in level.enemy_orbs.iter().enumerate() {
+                let position =
+                    level_orb.position_or(Vec3::new(combat_settings.enemy_x(i), 0.0, 0.0));
+                spawn_orb(
+                    &mut commands,
+                    position,
+                    &level_orb.orb,
+                    text_details.clone(),
+                    true,
+                );
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct LevelPlugin {
+    pub settings: LevelSettings,
+}
+
+impl Plugin for LevelPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(BasePlugin)
+            .insert_resource(self.settings.clone())
+            .add_asset::<Campaign>()
+            .init_asset_loader::<LevelLoader>()
+            .init_asset_loader::<CampaignLoader>()
+            .add_startup_system(load_campaign)
+            .add_system(load_campaign_levels)
+            .add_system(change_level);
+    }
+}
@@ -0,0 +1,189 @@
+use bevy::prelude::*;
+
+use crate::{
+    combat::Phase,
+    hud::{spawn_menu_button, spawn_start_menu, TextDetails},
+    level::{CampaignLevels, ChangeLevel, Level},
+    pause::{spawn_pause_menu, Paused},
+    save::SaveData,
+    BasePlugin,
+};
+
+// sent by the start and pause menus, which take themselves down first
+pub struct OpenLevelSelect;
+
+#[derive(Component)]
+pub struct LevelSelect {
+    // otherwise it was opened from the start menu
+    from_pause_menu: bool,
+}
+
+#[derive(Component, Clone, Copy)]
+enum LevelSelectButton {
+    Level(usize),
+    Locked,
+    Back,
+}
+
+fn open_level_select(
+    mut commands: Commands,
+    mut open_level_select_reader: EventReader<OpenLevelSelect>,
+    text_details: Res<TextDetails>,
+    campaign_levels: Res<CampaignLevels>,
+    levels: Res<Assets<Level>>,
+    save: Res<SaveData>,
+    paused: Res<State<Paused>>,
+) {
+    if open_level_select_reader.iter().last().is_none() {
+        return;
+    }
+
+    let text_style = TextStyle {
+        font_size: 25.,
+        ..text_details.text_style.clone().unwrap()
+    };
+
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                position_type: PositionType::Absolute,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                flex_direction: FlexDirection::ColumnReverse,
+                ..default()
+            },
+            color: Color::rgba(0.9, 0.9, 1.0, 0.8).into(),
+            ..default()
+        })
+        .insert(LevelSelect {
+            from_pause_menu: paused.current().0,
+        })
+        .with_children(|parent| {
+            parent.spawn_bundle(TextBundle::from_section(
+                "Select level",
+                TextStyle {
+                    font_size: 50.,
+                    ..text_style.clone()
+                },
+            ));
+
+            // endless levels are made up on the spot, so only the campaign is listed
+            let campaign =
+                (0..campaign_levels.levels.len()).filter(|&i| !campaign_levels.is_generated(i));
+            for index in campaign {
+                let locked = index > save.highest_unlocked;
+                let label = if locked {
+                    format!("Level {} - locked", index + 1)
+                } else {
+                    let name = campaign_levels
+                        .get(index, &levels)
+                        .map(|level| level.name.as_str())
+                        .unwrap_or_default();
+                    let best = match save.best_results.get(&index) {
+                        Some(result) => format!(
+                            "best: {} left, {} health",
+                            result.survivors, result.remaining_health
+                        ),
+                        None => "not beaten yet".to_string(),
+                    };
+                    format!("Level {} - {} | {}", index + 1, name, best)
+                };
+                spawn_level_button(parent, index, locked, label, text_style.clone());
+            }
+
+            spawn_menu_button(parent, LevelSelectButton::Back, "Back", text_style);
+        });
+}
+
+fn spawn_level_button(
+    parent: &mut ChildBuilder,
+    index: usize,
+    locked: bool,
+    label: String,
+    text_style: TextStyle,
+) {
+    let (button, color) = if locked {
+        (LevelSelectButton::Locked, Color::rgb(0.6, 0.6, 0.7))
+    } else {
+        (LevelSelectButton::Level(index), Color::rgb(0.4, 0.4, 0.7))
+    };
+
+    parent
+        .spawn_bundle(ButtonBundle {
+            style: Style {
+                size: Size::new(Val::Px(640.0), Val::Px(50.0)),
+                margin: UiRect::all(Val::Px(5.0)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            color: color.into(),
+            ..default()
+        })
+        .insert(button)
+        .with_children(|parent| {
+            parent.spawn_bundle(TextBundle::from_section(label, text_style));
+        });
+}
+
+fn level_select_buttons(
+    mut commands: Commands,
+    interaction_query: Query<(&Interaction, &LevelSelectButton), Changed<Interaction>>,
+    level_select_query: Query<(Entity, &LevelSelect)>,
+    mut paused: ResMut<State<Paused>>,
+    mut phase: ResMut<State<Phase>>,
+    mut change_level_writer: EventWriter<ChangeLevel>,
+    text_details: Res<TextDetails>,
+    save: Res<SaveData>,
+) {
+    for (interaction, button) in interaction_query.iter() {
+        if *interaction != Interaction::Clicked {
+            continue;
+        }
+
+        for (menu, level_select) in level_select_query.iter() {
+            match button {
+                LevelSelectButton::Locked => continue,
+                LevelSelectButton::Level(index) => {
+                    if *phase.current() == Phase::COMBAT {
+                        let _ = phase.set(Phase::PREP);
+                    }
+                    change_level_writer.send(ChangeLevel::Goto(*index));
+                    // leaving the pause state takes the menu down
+                    if level_select.from_pause_menu {
+                        let _ = paused.set(Paused(false));
+                        continue;
+                    }
+                }
+                // back to whichever menu opened it
+                LevelSelectButton::Back => {
+                    if level_select.from_pause_menu {
+                        spawn_pause_menu(&mut commands, &text_details);
+                    } else {
+                        spawn_start_menu(&mut commands, &text_details, save.current_level);
+                    }
+                }
+            }
+            commands.entity(menu).despawn_recursive();
+        }
+    }
+}
+
+fn despawn_level_select(mut commands: Commands, level_select_query: Query<(Entity, &LevelSelect)>) {
+    for (menu, level_select) in level_select_query.iter() {
+        if level_select.from_pause_menu {
+            commands.entity(menu).despawn_recursive();
+        }
+    }
+}
+
+pub struct LevelSelectPlugin;
+impl Plugin for LevelSelectPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(BasePlugin)
+            .add_system(open_level_select)
+            .add_system(level_select_buttons)
+            .add_system_set(SystemSet::on_exit(Paused(true)).with_system(despawn_level_select));
+    }
+}
@@ -0,0 +1,119 @@
+pub mod combat;
+pub mod combat_log;
+pub mod combat_speed;
+pub mod cursor;
+pub mod effects;
+pub mod formation;
+pub mod generator;
+pub mod history;
+pub mod hud;
+pub mod inspector;
+pub mod level;
+pub mod level_select;
+pub mod orb;
+pub mod orb_animation;
+pub mod pause;
+pub mod prediction;
+pub mod save;
+pub mod solver;
+
+use bevy::prelude::*;
+use combat::{Battle, CombatPlugin, CombatSettings, Phase};
+use combat_log::{CombatLog, CombatLogPlugin};
+use combat_speed::{CombatSpeed, CombatSpeedPlugin};
+use cursor::{Cursor, CursorPlugin};
+use effects::EffectsPlugin;
+use formation::FormationPlugin;
+use history::{HistoryPlugin, PrepHistory};
+use hud::{setup_text_details, HudPlugin, HudSettings, TextDetails};
+use inspector::InspectorPlugin;
+use level::{
+    CampaignLevels, ChangeLevel, CurrentLevel, Endless, Level, LevelCompleted, LevelPlugin,
+};
+use level_select::{LevelSelectPlugin, OpenLevelSelect};
+use orb::{ClosestCircle, MergeRule, OrbMerged, OrbPlugin, OrbSplit, StatCaps};
+use orb_animation::OrbAnimationPlugin;
+use pause::{PausePlugin, Paused};
+use prediction::PredictionPlugin;
+use save::{SaveData, SavePlugin};
+
+pub const PLAYER_COLOR: Color = Color::rgb(0.4, 0.4, 0.7);
+pub const ENEMY_COLOR: Color = Color::RED;
+
+// the animation rates were tuned as fractions per frame at this frame rate
+pub const TUNED_FPS: f32 = 60.0;
+
+// turns a per-frame lerp fraction into the one that covers the same ground over `delta`
+// seconds, so smoothing looks the same whatever the frame rate
+pub fn smoothing(fraction: f32, delta: f32) -> f32 {
+    1.0 - (1.0 - fraction).powf(delta * TUNED_FPS)
+}
+
+// marks the base as set up, so only the first plugin to add it does anything
+struct BaseReady;
+
+/// The states, resources and events that more than one plugin reads. Every
+/// plugin adds this before anything else, so each of them can be embedded on
+/// its own. The plugin that owns a resource still inserts its settings over
+/// the defaults set up here.
+pub struct BasePlugin;
+
+impl Plugin for BasePlugin {
+    fn build(&self, app: &mut App) {
+        if app.world.contains_resource::<BaseReady>() {
+            return;
+        }
+
+        app.insert_resource(BaseReady)
+            .add_state(Phase::PREP)
+            .add_state(Paused(false))
+            .init_resource::<HudSettings>()
+            .init_resource::<TextDetails>()
+            .add_startup_system(setup_text_details)
+            .init_resource::<Cursor>()
+            .init_resource::<SaveData>()
+            .init_resource::<CombatSettings>()
+            .init_resource::<Battle>()
+            .init_resource::<CombatLog>()
+            .init_resource::<CombatSpeed>()
+            .init_resource::<PrepHistory>()
+            .init_resource::<ClosestCircle>()
+            .init_resource::<MergeRule>()
+            .init_resource::<StatCaps>()
+            .init_resource::<CurrentLevel>()
+            .init_resource::<Endless>()
+            .init_resource::<CampaignLevels>()
+            .add_asset::<Level>()
+            .add_event::<ChangeLevel>()
+            .add_event::<LevelCompleted>()
+            .add_event::<OpenLevelSelect>()
+            .add_event::<OrbMerged>()
+            .add_event::<OrbSplit>();
+    }
+}
+
+/// Everything Orber needs on top of bevy's `DefaultPlugins` and lyon's
+/// `ShapePlugin`, with each plugin on its default settings.
+pub struct CorePlugins;
+
+impl PluginGroup for CorePlugins {
+    fn build(&mut self, group: &mut bevy::app::PluginGroupBuilder) {
+        group
+            .add(HudPlugin::default())
+            .add(OrbPlugin::default())
+            .add(CombatPlugin::default())
+            .add(LevelPlugin::default())
+            .add(CursorPlugin)
+            .add(SavePlugin)
+            .add(HistoryPlugin)
+            .add(PausePlugin)
+            .add(LevelSelectPlugin)
+            .add(CombatSpeedPlugin)
+            .add(CombatLogPlugin)
+            .add(EffectsPlugin)
+            .add(OrbAnimationPlugin)
+            .add(PredictionPlugin)
+            .add(FormationPlugin)
+            .add(InspectorPlugin);
+    }
+}
@@ -0,0 +1,931 @@
+use std::{
+    fmt,
+    iter::Sum,
+    ops::{Add, AddAssign, ControlFlow, Sub, SubAssign},
+};
+
+use bevy::{input::mouse::MouseMotion, prelude::*};
+use bevy_easings::Lerp;
+use bevy_prototype_lyon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    combat::Phase, cursor::Cursor, effects, hud::TextDetails, orb_animation, pause::unpaused,
+    smoothing, BasePlugin, ENEMY_COLOR, PLAYER_COLOR,
+};
+
+// wide enough that no merge in a real level gets near the edge, merges past a
+// level's `StatCaps` are refused before that anyway. All arithmetic saturates
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(transparent)]
+pub struct Stat(pub i32);
+
+impl Stat {
+    pub const MIN: Stat = Stat(i32::MIN);
+    pub const MAX: Stat = Stat(i32::MAX);
+}
+
+impl Add for Stat {
+    type Output = Stat;
+
+    fn add(self, other: Stat) -> Stat {
+        Stat(self.0.saturating_add(other.0))
+    }
+}
+
+impl Sub for Stat {
+    type Output = Stat;
+
+    fn sub(self, other: Stat) -> Stat {
+        Stat(self.0.saturating_sub(other.0))
+    }
+}
+
+impl AddAssign for Stat {
+    fn add_assign(&mut self, other: Stat) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for Stat {
+    fn sub_assign(&mut self, other: Stat) {
+        *self = *self - other;
+    }
+}
+
+impl Sum for Stat {
+    fn sum<I: Iterator<Item = Stat>>(iter: I) -> Stat {
+        iter.fold(Stat(0), Add::add)
+    }
+}
+
+impl fmt::Display for Stat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[derive(Component, Clone, Debug, Default, Deserialize)]
+pub struct Orb {
+    pub damage: Stat,
+    pub health: Stat,
+    #[serde(default)]
+    pub children: Vec<Orb>,
+}
+
+/// How two orbs' stats combine when merged. The merged orb keeps both
+/// originals as children, so splitting always gives them back unchanged
+/// whatever the rule.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+pub enum MergeRule {
+    #[default]
+    Additive,
+    Multiplicative,
+    Max,
+    Average,
+    // additive, but every merge costs a point of each stat
+    Tax,
+}
+
+impl MergeRule {
+    // exact for any two stats, the caller decides what to do when it doesn't fit
+    fn combine(&self, a: Stat, b: Stat) -> i64 {
+        let (a, b) = (i64::from(a.0), i64::from(b.0));
+        match self {
+            MergeRule::Additive => a + b,
+            MergeRule::Multiplicative => a * b,
+            MergeRule::Max => a.max(b),
+            MergeRule::Average => (a + b) / 2,
+            MergeRule::Tax => a + b - 1,
+        }
+    }
+}
+
+/// The highest damage and health a merge is allowed to produce.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct StatCaps {
+    pub damage: Stat,
+    pub health: Stat,
+}
+
+impl Default for StatCaps {
+    fn default() -> Self {
+        StatCaps {
+            damage: Stat(999),
+            health: Stat(999),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OverCap {
+    pub stat: &'static str,
+    pub cap: Stat,
+}
+
+impl fmt::Display for OverCap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} would pass the cap of {}", self.stat, self.cap)
+    }
+}
+
+fn saturate(value: i64) -> Stat {
+    Stat(value.clamp(i64::from(i32::MIN), i64::from(i32::MAX)) as i32)
+}
+
+impl fmt::Display for MergeRule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MergeRule::Additive => write!(f, "additive"),
+            MergeRule::Multiplicative => write!(f, "multiplicative"),
+            MergeRule::Max => write!(f, "max of both"),
+            MergeRule::Average => write!(f, "average"),
+            MergeRule::Tax => write!(f, "merge tax (-1 per merge)"),
+        }
+    }
+}
+
+impl Orb {
+    // saturates instead of failing, for rebuilding orbs that were already merged once
+    pub fn merge(a: &Orb, b: &Orb, rule: MergeRule) -> Orb {
+        Orb {
+            damage: saturate(rule.combine(a.damage, b.damage)),
+            health: saturate(rule.combine(a.health, b.health)),
+            children: vec![a.clone(), b.clone()],
+        }
+    }
+
+    pub fn try_merge(a: &Orb, b: &Orb, rule: MergeRule, caps: StatCaps) -> Result<Orb, OverCap> {
+        if rule.combine(a.damage, b.damage) > i64::from(caps.damage.0) {
+            return Err(OverCap {
+                stat: "damage",
+                cap: caps.damage,
+            });
+        }
+        if rule.combine(a.health, b.health) > i64::from(caps.health.0) {
+            return Err(OverCap {
+                stat: "health",
+                cap: caps.health,
+            });
+        }
+        Ok(Orb::merge(a, b, rule))
+    }
+
+    // how many orbs this one comes apart into when split all the way down
+    pub fn leaf_count(&self) -> usize {
+        if self.children.is_empty() {
+            1
+        } else {
+            self.children.iter().map(Orb::leaf_count).sum()
+        }
+    }
+
+    pub fn leaves(&self) -> Vec<Orb> {
+        if self.children.is_empty() {
+            vec![self.clone()]
+        } else {
+            self.children.iter().flat_map(Orb::leaves).collect()
+        }
+    }
+
+    // paths of child indices to every orb below this one, in the order `tree` lists them
+    pub fn sub_orb_paths(&self) -> Vec<Vec<usize>> {
+        let mut paths = vec![];
+        for (i, child) in self.children.iter().enumerate() {
+            paths.push(vec![i]);
+            for mut path in child.sub_orb_paths() {
+                path.insert(0, i);
+                paths.push(path);
+            }
+        }
+        paths
+    }
+
+    /// Takes the sub-orb at `path` out of the tree, returning the rest rebuilt
+    /// from the remaining pieces under `rule` and the detached orb. The root
+    /// itself can't be detached.
+    pub fn detach(&self, path: &[usize], rule: MergeRule) -> Option<(Orb, Orb)> {
+        let (&first, rest) = path.split_first()?;
+        let child = self.children.get(first)?;
+        let (remaining, detached) = if rest.is_empty() {
+            (None, child.clone())
+        } else {
+            let (remaining, detached) = child.detach(rest, rule)?;
+            (Some(remaining), detached)
+        };
+
+        let rebuilt = self
+            .children
+            .iter()
+            .enumerate()
+            .filter_map(|(i, child)| {
+                if i == first {
+                    remaining.clone()
+                } else {
+                    Some(child.clone())
+                }
+            })
+            .reduce(|a, b| Orb::merge(&a, &b, rule))?;
+        Some((rebuilt, detached))
+    }
+
+    /// The merge tree drawn out one orb per line, children indented under
+    /// their parent.
+    pub fn tree(&self) -> String {
+        let mut lines = vec![self.to_string()];
+        self.tree_children("", &mut lines);
+        lines.join("\n")
+    }
+
+    fn tree_children(&self, indent: &str, lines: &mut Vec<String>) {
+        for (i, child) in self.children.iter().enumerate() {
+            let last = i + 1 == self.children.len();
+            lines.push(format!(
+                "{}{}{}",
+                indent,
+                if last { "└─ " } else { "├─ " },
+                child
+            ));
+            child.tree_children(
+                &format!("{}{}", indent, if last { "   " } else { "│  " }),
+                lines,
+            );
+        }
+    }
+}
+
+// damage on the left, health on the right, the same way the orb labels are laid out
+impl fmt::Display for Orb {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}", self.damage, self.health)
+    }
+}
+
+#[derive(Clone)]
+pub struct OrbSettings {
+    // how close the cursor has to be to pick an orb up
+    pub grab_radius: f32,
+    // how close a dragged orb has to be dropped to another to merge with it
+    pub merge_radius: f32,
+}
+
+impl Default for OrbSettings {
+    fn default() -> Self {
+        OrbSettings {
+            grab_radius: 30.0,
+            merge_radius: 30.0,
+        }
+    }
+}
+
+// sent once the merge animation is over and the merged orb is on the board
+pub struct OrbMerged {
+    pub entity: Entity,
+    pub orb: Orb,
+}
+
+// sent as the orb comes apart, `pieces` land a moment later
+pub struct OrbSplit {
+    pub orb: Orb,
+    pub pieces: Vec<Orb>,
+    pub position: Vec3,
+}
+
+#[derive(Component)]
+pub struct PlayerOrb;
+#[derive(Component)]
+pub struct EnemyOrb;
+
+// the label follows this stat of the `Orb` on `orb`, see `update_stat_labels`
+#[derive(Component)]
+pub struct StatLabel {
+    pub orb: Entity,
+    pub stat: OrbStat,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum OrbStat {
+    Damage,
+    Health,
+}
+
+impl OrbStat {
+    pub fn value(&self, orb: &Orb) -> Stat {
+        match self {
+            OrbStat::Damage => orb.damage,
+            OrbStat::Health => orb.health,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct HealthDisplay;
+#[derive(Component)]
+pub struct DamageDisplay;
+
+pub fn spawn_orb(
+    commands: &mut Commands,
+    position: Vec3,
+    orb: &Orb,
+    text_details: TextDetails,
+    is_enemy: bool,
+) -> Entity {
+    let parent = if is_enemy {
+        commands
+            .spawn_bundle(GeometryBuilder::build_as(
+                &shapes::Circle {
+                    center: Vec2::new(0., 0.),
+                    radius: 50.,
+                },
+                DrawMode::Outlined {
+                    fill_mode: FillMode::color(ENEMY_COLOR),
+                    outline_mode: StrokeMode::new(Color::BLACK, 5.0),
+                },
+                Transform {
+                    translation: position,
+                    ..default()
+                },
+            ))
+            .insert(orb.clone())
+            .insert(EnemyOrb)
+            .id()
+    } else {
+        commands
+            .spawn_bundle(GeometryBuilder::build_as(
+                &shapes::Circle {
+                    center: Vec2::new(0., 0.),
+                    radius: 30.,
+                },
+                DrawMode::Outlined {
+                    fill_mode: FillMode::color(PLAYER_COLOR),
+                    outline_mode: StrokeMode::new(Color::BLACK, 5.0),
+                },
+                Transform {
+                    translation: position,
+                    ..default()
+                },
+            ))
+            .insert(orb.clone())
+            .insert(PlayerOrb)
+            .id()
+    };
+
+    let health_child = commands
+        .spawn_bundle(GeometryBuilder::build_as(
+            &shapes::Circle {
+                center: Vec2::new(0., 0.),
+                radius: 12.,
+            },
+            DrawMode::Outlined {
+                fill_mode: FillMode::color(Color::RED),
+                outline_mode: StrokeMode::new(Color::BLACK, 5.0),
+            },
+            Transform {
+                translation: if is_enemy {
+                    Vec3::new(34., -34., 0.1)
+                } else {
+                    Vec3::new(22., -22., 0.1)
+                },
+                ..default()
+            },
+        ))
+        .insert(HealthDisplay)
+        .id();
+    let damage_child = commands
+        .spawn_bundle(GeometryBuilder::build_as(
+            &shapes::Circle {
+                center: Vec2::new(0., 0.),
+                radius: 12.,
+            },
+            DrawMode::Outlined {
+                fill_mode: FillMode::color(Color::ALICE_BLUE),
+                outline_mode: StrokeMode::new(Color::BLACK, 5.0),
+            },
+            Transform {
+                translation: if is_enemy {
+                    Vec3::new(-34., -34., 0.1)
+                } else {
+                    Vec3::new(-22., -22., 0.1)
+                },
+                ..default()
+            },
+        ))
+        .insert(DamageDisplay)
+        .id();
+    let health_text = commands
+        .spawn_bundle(Text2dBundle {
+            text: Text::from_section(
+                format!("{}", orb.health),
+                text_details.text_style.clone().unwrap(),
+            )
+            .with_alignment(text_details.text_alignment.unwrap()),
+            transform: Transform {
+                translation: Vec3::new(0., 0., 5.),
+                ..default()
+            },
+            ..default()
+        })
+        .insert(StatLabel {
+            orb: parent,
+            stat: OrbStat::Health,
+        })
+        .id();
+    commands.entity(health_child).add_child(health_text);
+    let damage_text = commands
+        .spawn_bundle(Text2dBundle {
+            text: Text::from_section(
+                format!("{}", orb.damage),
+                text_details.text_style.clone().unwrap(),
+            )
+            .with_alignment(text_details.text_alignment.unwrap()),
+            transform: Transform {
+                translation: Vec3::new(0., 0., 5.),
+                ..default()
+            },
+            ..default()
+        })
+        .insert(StatLabel {
+            orb: parent,
+            stat: OrbStat::Damage,
+        })
+        .id();
+    commands.entity(damage_child).add_child(damage_text);
+    commands.entity(parent).add_child(health_child);
+    commands.entity(parent).add_child(damage_child);
+    parent
+}
+
+#[derive(Debug, Default)]
+pub struct ClosestCircle {
+    pub data: Option<ClosestCircleData>,
+}
+
+#[derive(Debug)]
+pub struct ClosestCircleData {
+    pub entity: Entity,
+    pub distance: f32,
+    pub orb: Orb,
+}
+
+pub fn update(
+    mut player_orb_query: Query<(&mut Transform, &mut DrawMode, Entity, &Orb), With<PlayerOrb>>,
+    cursor: Res<Cursor>,
+    mut mouse_motion_event_reader: EventReader<MouseMotion>,
+    mouse_buttons: Res<Input<MouseButton>>,
+    mut closest_circle: ResMut<ClosestCircle>,
+    mut commands: Commands,
+    phase: Res<State<Phase>>,
+    text_details: Res<TextDetails>,
+    time: Res<Time>,
+    merge_rule: Res<MergeRule>,
+    stat_caps: Res<StatCaps>,
+    settings: Res<OrbSettings>,
+    mut split_writer: EventWriter<OrbSplit>,
+) {
+    let prep_mode = *phase.current() == Phase::PREP;
+    let delta = time.delta_seconds();
+
+    let mut distance_to_each_entity: Vec<ClosestCircleData> = vec![];
+
+    for (transform, _, entity, orb) in player_orb_query.iter_mut() {
+        let center = transform.translation.truncate();
+
+        let mouse_distance_to_center = cursor.0.distance(center);
+        distance_to_each_entity.push(ClosestCircleData {
+            entity,
+            distance: mouse_distance_to_center,
+            orb: orb.clone(),
+        })
+    }
+
+    distance_to_each_entity.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+    let closest = distance_to_each_entity.first();
+
+    if !mouse_buttons.pressed(MouseButton::Left) && prep_mode {
+        for (transform, _, entity, orb) in player_orb_query.iter_mut() {
+            if let Some(closest) = closest {
+                let center = transform.translation.truncate();
+
+                let mouse_distance_to_center = cursor.0.distance(center);
+
+                if closest.entity == entity {
+                    if mouse_distance_to_center < settings.grab_radius {
+                        closest_circle.data = Some(ClosestCircleData {
+                            entity,
+                            distance: mouse_distance_to_center,
+                            orb: orb.clone(),
+                        })
+                    } else {
+                        closest_circle.data = None;
+                    }
+                } else {
+                    if mouse_buttons.just_released(MouseButton::Left) && prep_mode {
+                        if mouse_distance_to_center < settings.merge_radius {
+                            match Orb::try_merge(orb, &closest.orb, *merge_rule, *stat_caps) {
+                                Ok(new_orb) => orb_animation::start_merge(
+                                    &mut commands,
+                                    [entity, closest.entity],
+                                    new_orb,
+                                    cursor.0.extend(0.0),
+                                ),
+                                // the orbs are left where they were dropped
+                                Err(over_cap) => effects::spawn_floating_text(
+                                    &mut commands,
+                                    &text_details,
+                                    cursor.0.extend(10.0),
+                                    format!("can't merge, {}", over_cap),
+                                    22.,
+                                    Color::rgb(0.8, 0.1, 0.1),
+                                ),
+                            }
+
+                            closest_circle.data = None;
+                            return;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // split orbs are placed clear of the rest
+    let orb_positions: Vec<(Entity, Vec3)> = player_orb_query
+        .iter()
+        .map(|(t, _, entity, _)| (entity, t.translation))
+        .collect();
+
+    for (mut t, mut d, entity, orb) in player_orb_query.iter_mut() {
+        if let Some(closest) = &closest_circle.data {
+            if closest.entity == entity {
+                if let DrawMode::Outlined {
+                    ref mut fill_mode,
+                    outline_mode: _,
+                } = *d
+                {
+                    modify_color(fill_mode, 0.6, 0.6, 1.0, 0.05, delta)
+                }
+
+                t.translation.z = 1.0;
+
+                follow_mouse(
+                    &mouse_buttons,
+                    &mut t,
+                    &cursor,
+                    &mut mouse_motion_event_reader,
+                    &prep_mode,
+                    delta,
+                );
+
+                if mouse_buttons.just_pressed(MouseButton::Right) && prep_mode {
+                    if let ControlFlow::Break(_) = split_orb(
+                        orb,
+                        &mut commands,
+                        &t,
+                        entity,
+                        text_details.clone(),
+                        &orb_positions,
+                        &mut split_writer,
+                    ) {
+                        closest_circle.data = None;
+                        return;
+                    }
+                }
+
+                if prep_mode {
+                    continue;
+                }
+            }
+        }
+
+        if let DrawMode::Outlined {
+            ref mut fill_mode,
+            outline_mode: _,
+        } = *d
+        {
+            modify_color(fill_mode, 0.4, 0.4, 0.7, 0.1, delta)
+        }
+
+        t.translation.z = 0.0;
+    }
+
+    let mut iter = player_orb_query.iter_combinations_mut();
+    while let Some([(t1, _, e1, _), (t2, _, e2, _)]) = iter.fetch_next() {
+        if let Some(closest) = closest {
+            if (closest.entity == e1 || closest.entity == e2)
+                && mouse_buttons.pressed(MouseButton::Left)
+                && prep_mode
+            {
+                continue;
+            }
+        }
+
+        push_apart(t2, t1, delta);
+    }
+
+    if mouse_buttons.pressed(MouseButton::Left) && prep_mode {
+        let mut combine_ready = false;
+        for (t, mut d, entity, _) in player_orb_query.iter_mut() {
+            if let Some(data) = &closest {
+                if data.entity != entity {
+                    let center = t.translation.truncate();
+                    let mouse_distance_to_center = cursor.0.distance(center);
+
+                    if mouse_distance_to_center <= settings.merge_radius {
+                        combine_ready = true;
+                        brighten(&mut d, delta);
+                        increase_line_width(&mut d, delta);
+                    } else {
+                        decrease_line_width(&mut d, delta);
+                    }
+                }
+            }
+        }
+        for (_, mut d, entity, _) in player_orb_query.iter_mut() {
+            if let Some(data) = &closest {
+                if data.entity == entity {
+                    if combine_ready {
+                        if let DrawMode::Outlined {
+                            ref mut fill_mode,
+                            outline_mode: _,
+                        } = *d
+                        {
+                            modify_color(fill_mode, 0.8, 0.8, 1.0, 0.05, delta);
+                            increase_line_width(&mut d, delta);
+                        }
+                    } else {
+                        if let DrawMode::Outlined {
+                            fill_mode: _,
+                            outline_mode: _,
+                        } = *d
+                        {
+                            decrease_line_width(&mut d, delta);
+                        }
+                    }
+                }
+            }
+        }
+    } else {
+        for (_, mut d, _, _) in player_orb_query.iter_mut() {
+            if !prep_mode {
+                decrease_line_width(&mut d, delta)
+            }
+        }
+    }
+}
+
+// any change to an orb's stats shows up on its labels
+fn update_stat_labels(
+    orb_query: Query<&Orb, Changed<Orb>>,
+    mut label_query: Query<(&StatLabel, &mut Text)>,
+) {
+    for (label, mut text) in label_query.iter_mut() {
+        if let Ok(orb) = orb_query.get(label.orb) {
+            let value = label.stat.value(orb).to_string();
+            if text.sections[0].value != value {
+                text.sections[0].value = value;
+            }
+        }
+    }
+}
+
+fn split_orb(
+    orb: &Orb,
+    commands: &mut Commands,
+    t: &Mut<Transform>,
+    entity: Entity,
+    text_details: TextDetails,
+    orb_positions: &[(Entity, Vec3)],
+    split_writer: &mut EventWriter<OrbSplit>,
+) -> ControlFlow<()> {
+    if orb.children.is_empty() {
+        return ControlFlow::Continue(());
+    }
+    let others: Vec<Vec3> = orb_positions
+        .iter()
+        .filter(|(other, _)| *other != entity)
+        .map(|(_, position)| *position)
+        .collect();
+    orb_animation::burst_split(
+        commands,
+        t.translation,
+        &orb.children,
+        &others,
+        text_details,
+    );
+    commands.entity(entity).despawn_recursive();
+    split_writer.send(OrbSplit {
+        orb: orb.clone(),
+        pieces: orb.children.clone(),
+        position: t.translation,
+    });
+    return ControlFlow::Break(());
+}
+
+// nudges overlapping orbs apart, scaled to the frame time like the lerps
+fn push_apart(mut t2: Mut<Transform>, mut t1: Mut<Transform>, delta: f32) {
+    let offset = t2.translation - t1.translation;
+    if offset.length() < 100. {
+        let push =
+            |d: f32| (100.0 - d.abs()) / 2.0 * d.signum() * smoothing(d.abs() / 1000.0, delta);
+        let push = Vec3::new(push(offset.x), push(offset.y), 0.0);
+        t1.translation -= push;
+        t2.translation += push;
+    }
+}
+
+fn modify_color(fill_mode: &mut FillMode, r: f32, g: f32, b: f32, rate: f32, delta: f32) {
+    let rate = smoothing(rate, delta);
+    fill_mode.color = Color::rgb(
+        fill_mode.color.r().lerp(&r, &rate),
+        fill_mode.color.g().lerp(&g, &rate),
+        fill_mode.color.b().lerp(&b, &rate),
+    );
+}
+
+fn brighten(d: &mut Mut<DrawMode>, delta: f32) {
+    if let DrawMode::Outlined {
+        ref mut fill_mode,
+        outline_mode: _,
+    } = **d
+    {
+        modify_color(fill_mode, 1.0, 1.0, 1.0, 0.2, delta);
+    }
+}
+
+fn increase_line_width(d: &mut Mut<DrawMode>, delta: f32) {
+    if let DrawMode::Outlined {
+        fill_mode: _ode,
+        ref mut outline_mode,
+    } = **d
+    {
+        outline_mode.options.line_width = outline_mode
+            .options
+            .line_width
+            .lerp(&10.0, &smoothing(0.2, delta));
+    }
+}
+
+fn decrease_line_width(d: &mut Mut<DrawMode>, delta: f32) {
+    if let DrawMode::Outlined {
+        fill_mode: _,
+        ref mut outline_mode,
+    } = **d
+    {
+        outline_mode.options.line_width = outline_mode
+            .options
+            .line_width
+            .lerp(&5.0, &smoothing(0.2, delta));
+    }
+}
+
+fn follow_mouse(
+    mouse_buttons: &Res<Input<MouseButton>>,
+    t: &mut Mut<Transform>,
+    cursor: &Res<Cursor>,
+    mouse_motion_event_reader: &mut EventReader<MouseMotion>,
+    controls_disabled: &bool,
+    delta: f32,
+) {
+    if mouse_buttons.pressed(MouseButton::Left) && *controls_disabled {
+        let diff = t.translation.truncate() - cursor.0;
+
+        t.translation -= (diff * smoothing(0.1, delta)).extend(0.0);
+
+        for mouse_motion_event in mouse_motion_event_reader.iter() {
+            t.translation += Vec3::new(mouse_motion_event.delta.x, -mouse_motion_event.delta.y, 0.0)
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct OrbPlugin {
+    pub settings: OrbSettings,
+}
+
+impl Plugin for OrbPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(BasePlugin)
+            .insert_resource(self.settings.clone())
+            .add_system(update.with_run_criteria(unpaused))
+            .add_system(update_stat_labels);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn orb(damage: i32, health: i32) -> Orb {
+        Orb {
+            damage: Stat(damage),
+            health: Stat(health),
+            ..Orb::default()
+        }
+    }
+
+    fn stats(orbs: &[Orb]) -> Vec<(i32, i32)> {
+        orbs.iter()
+            .map(|orb| (orb.damage.0, orb.health.0))
+            .collect()
+    }
+
+    fn combine(rule: MergeRule, a: i32, b: i32) -> i64 {
+        rule.combine(Stat(a), Stat(b))
+    }
+
+    const RULES: [MergeRule; 5] = [
+        MergeRule::Additive,
+        MergeRule::Multiplicative,
+        MergeRule::Max,
+        MergeRule::Average,
+        MergeRule::Tax,
+    ];
+
+    #[test]
+    fn each_rule_combines_stats_its_own_way() {
+        assert_eq!(combine(MergeRule::Additive, 3, 4), 7);
+        assert_eq!(combine(MergeRule::Multiplicative, 3, 4), 12);
+        assert_eq!(combine(MergeRule::Max, 3, 4), 4);
+        assert_eq!(combine(MergeRule::Average, 3, 4), 3);
+        assert_eq!(combine(MergeRule::Tax, 3, 4), 6);
+
+        // cursed orbs bring their negative health along
+        assert_eq!(combine(MergeRule::Additive, 3, -2), 1);
+        assert_eq!(combine(MergeRule::Multiplicative, 3, -2), -6);
+        assert_eq!(combine(MergeRule::Max, 3, -2), 3);
+        assert_eq!(combine(MergeRule::Average, 3, -2), 0);
+        assert_eq!(combine(MergeRule::Tax, 3, -2), 0);
+    }
+
+    #[test]
+    fn merges_past_the_caps_are_refused() {
+        let caps = StatCaps {
+            damage: Stat(6),
+            health: Stat(6),
+        };
+        let merged = Orb::try_merge(&orb(3, 3), &orb(3, 3), MergeRule::Additive, caps).unwrap();
+        assert_eq!(stats(&[merged]), vec![(6, 6)]);
+
+        let refused = Orb::try_merge(&orb(3, 4), &orb(3, 3), MergeRule::Additive, caps);
+        assert_eq!(
+            refused.unwrap_err(),
+            OverCap {
+                stat: "health",
+                cap: Stat(6)
+            }
+        );
+        assert_eq!(
+            Orb::merge(&orb(i32::MAX, 1), &orb(1, 1), MergeRule::Additive).damage,
+            Stat::MAX
+        );
+    }
+
+    #[test]
+    fn splitting_gives_back_the_original_children() {
+        let (a, b) = (orb(2, 3), orb(3, -2));
+        for rule in RULES {
+            let merged = Orb::merge(&a, &b, rule);
+            assert_eq!(stats(&merged.children), vec![(2, 3), (3, -2)], "{}", rule);
+            assert_eq!(stats(&merged.leaves()), vec![(2, 3), (3, -2)], "{}", rule);
+        }
+    }
+
+    #[test]
+    fn detach_rebuilds_the_rest_under_the_rule() {
+        let (a, b, c) = (orb(2, 3), orb(3, 1), orb(1, 4));
+        for rule in RULES {
+            let tree = Orb::merge(&Orb::merge(&a, &b, rule), &c, rule);
+            assert_eq!(
+                stats(&tree.leaves()),
+                vec![(2, 3), (3, 1), (1, 4)],
+                "{}",
+                rule
+            );
+
+            // a direct child comes off whole and leaves its sibling untouched
+            let (rest, detached) = tree.detach(&[1], rule).unwrap();
+            assert_eq!(stats(&[detached]), vec![(1, 4)], "{}", rule);
+            assert_eq!(stats(&rest.children), vec![(2, 3), (3, 1)], "{}", rule);
+
+            // a deeper piece takes its branch apart, the rest is merged again
+            let (rest, detached) = tree.detach(&[0, 1], rule).unwrap();
+            let remerged = Orb::merge(&a, &c, rule);
+            assert_eq!(stats(&[detached]), vec![(3, 1)], "{}", rule);
+            assert_eq!(
+                (rest.damage, rest.health),
+                (remerged.damage, remerged.health),
+                "{}",
+                rule
+            );
+            assert_eq!(stats(&rest.children), vec![(2, 3), (1, 4)], "{}", rule);
+        }
+
+        assert!(orb(1, 1).detach(&[0], MergeRule::Additive).is_none());
+        assert!(Orb::merge(&a, &b, MergeRule::Additive)
+            .detach(&[], MergeRule::Additive)
+            .is_none());
+    }
+}
@@ -0,0 +1,217 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+
+use crate::{
+    effects::insert_if_alive,
+    hud::TextDetails,
+    orb::{spawn_orb, Orb, OrbMerged, PlayerOrb},
+    pause::unpaused,
+    BasePlugin,
+};
+
+const MERGE_SECONDS: f32 = 0.4;
+const SWIRL_TURNS: f32 = 1.5;
+const SPLIT_SECONDS: f32 = 0.35;
+// centre to centre, two player orbs are 60 across
+const SPLIT_SPACING: f32 = 80.0;
+
+// orbs sit out of play while they animate, so combat and undo wait for this to clear
+#[derive(Component)]
+pub struct Animating;
+
+// the merged orb only exists once the swirl is over, see `swirl_merges`
+#[derive(Component)]
+struct PendingMerge {
+    sources: [Entity; 2],
+    result: Orb,
+    center: Vec3,
+    timer: Timer,
+}
+
+#[derive(Component)]
+struct Swirl {
+    // from the centre, taken the first frame the swirl runs
+    offset: Option<Vec3>,
+}
+
+// driven by hand like the swirl, so it holds still while the game is paused
+#[derive(Component)]
+struct SplitBurst {
+    from: Vec3,
+    target: Vec3,
+    timer: Timer,
+}
+
+pub fn start_merge(commands: &mut Commands, sources: [Entity; 2], result: Orb, center: Vec3) {
+    commands
+        .spawn()
+        .insert(PendingMerge {
+            sources,
+            result,
+            center,
+            timer: Timer::from_seconds(MERGE_SECONDS, false),
+        })
+        .insert(Animating);
+
+    for source in sources {
+        commands
+            .entity(source)
+            .remove::<PlayerOrb>()
+            .insert(Swirl { offset: None })
+            .insert(Animating);
+    }
+}
+
+fn ease_in_out(t: f32) -> f32 {
+    if t < 0.5 {
+        2.0 * t * t
+    } else {
+        1.0 - (-2.0 * t + 2.0).powi(2) / 2.0
+    }
+}
+
+// overshoots the end a little before settling on it
+fn back_out(t: f32) -> f32 {
+    const OVERSHOOT: f32 = 1.70158;
+    1.0 + (OVERSHOOT + 1.0) * (t - 1.0).powi(3) + OVERSHOOT * (t - 1.0).powi(2)
+}
+
+fn swirl_merges(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut merge_query: Query<(Entity, &mut PendingMerge)>,
+    mut swirl_query: Query<(&mut Swirl, &mut Transform)>,
+    text_details: Res<TextDetails>,
+    mut merged_writer: EventWriter<OrbMerged>,
+) {
+    for (merge_entity, mut merge) in merge_query.iter_mut() {
+        // the sources are gone if the level changed mid-swirl, so the merge never happens
+        if merge
+            .sources
+            .iter()
+            .any(|&source| swirl_query.get(source).is_err())
+        {
+            commands.entity(merge_entity).despawn();
+            continue;
+        }
+
+        merge.timer.tick(time.delta());
+        let progress = ease_in_out(merge.timer.percent());
+
+        for &source in &merge.sources {
+            if let Ok((mut swirl, mut t)) = swirl_query.get_mut(source) {
+                let offset = *swirl.offset.get_or_insert(t.translation - merge.center);
+                let turned = Quat::from_rotation_z(progress * SWIRL_TURNS * TAU) * offset;
+                t.translation = merge.center + turned * (1.0 - progress) + Vec3::Z;
+                t.scale = Vec3::splat(1.0 - 0.4 * progress);
+            }
+        }
+
+        if merge.timer.finished() {
+            for &source in &merge.sources {
+                commands.entity(source).despawn_recursive();
+            }
+            commands.entity(merge_entity).despawn();
+            let entity = spawn_orb(
+                &mut commands,
+                merge.center,
+                &merge.result,
+                text_details.clone(),
+                false,
+            );
+            merged_writer.send(OrbMerged {
+                entity,
+                orb: merge.result.clone(),
+            });
+        }
+    }
+}
+
+// spread around `from`, turned to keep as far as possible from the orbs already there
+fn split_targets(from: Vec3, count: usize, others: &[Vec3]) -> Vec<Vec3> {
+    let radius = if count < 2 {
+        0.0
+    } else {
+        (SPLIT_SPACING / 2.0 / (TAU / 2.0 / count as f32).sin()).max(SPLIT_SPACING / 2.0)
+    };
+    let ring = |turn: f32| -> Vec<Vec3> {
+        (0..count)
+            .map(|i| {
+                let angle = turn + i as f32 / count as f32 * TAU;
+                from + Vec3::new(angle.cos(), angle.sin(), 0.0) * radius
+            })
+            .collect()
+    };
+    let clearance = |targets: &[Vec3]| -> f32 {
+        targets
+            .iter()
+            .flat_map(|target| {
+                others
+                    .iter()
+                    .map(move |other| target.truncate().distance(other.truncate()))
+            })
+            .fold(f32::MAX, f32::min)
+    };
+
+    (0..12)
+        .map(|step| ring(step as f32 / 12.0 * TAU))
+        .max_by(|a, b| clearance(a).total_cmp(&clearance(b)))
+        .unwrap_or_default()
+}
+
+pub fn burst_split(
+    commands: &mut Commands,
+    from: Vec3,
+    children: &[Orb],
+    others: &[Vec3],
+    text_details: TextDetails,
+) {
+    for (child, target) in children
+        .iter()
+        .zip(split_targets(from, children.len(), others))
+    {
+        let entity = spawn_orb(commands, from, child, text_details.clone(), false);
+        commands
+            .entity(entity)
+            .remove::<PlayerOrb>()
+            .insert(Animating)
+            .insert(SplitBurst {
+                from,
+                target,
+                timer: Timer::from_seconds(SPLIT_SECONDS, false),
+            });
+    }
+}
+
+fn land_split_orbs(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut burst_query: Query<(Entity, &mut SplitBurst, &mut Transform)>,
+) {
+    for (entity, mut burst, mut t) in burst_query.iter_mut() {
+        burst.timer.tick(time.delta());
+        let progress = back_out(burst.timer.percent());
+        t.translation = burst.from.lerp(burst.target, progress);
+
+        if burst.timer.finished() {
+            commands
+                .entity(entity)
+                .remove::<SplitBurst>()
+                .remove::<Animating>();
+            insert_if_alive(&mut commands, entity, PlayerOrb);
+        }
+    }
+}
+
+pub struct OrbAnimationPlugin;
+impl Plugin for OrbAnimationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(BasePlugin).add_system_set(
+            SystemSet::new()
+                .with_run_criteria(unpaused)
+                .with_system(swirl_merges)
+                .with_system(land_split_orbs),
+        );
+    }
+}
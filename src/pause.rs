@@ -0,0 +1,176 @@
+use bevy::{
+    app::AppExit,
+    ecs::schedule::{ShouldRun, StateData},
+    prelude::*,
+    window::WindowFocused,
+};
+
+use crate::{
+    combat::Phase,
+    hud::{spawn_menu_button, TextDetails},
+    level::ChangeLevel,
+    level_select::OpenLevelSelect,
+    BasePlugin,
+};
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Paused(pub bool);
+
+pub fn unpaused(paused: Res<State<Paused>>) -> ShouldRun {
+    if paused.current().0 {
+        ShouldRun::No
+    } else {
+        ShouldRun::Yes
+    }
+}
+
+// `State::on_update`, except it also holds still while the game is paused
+pub fn on_update_unpaused<S: StateData>(
+    state: S,
+) -> impl FnMut(Res<State<S>>, Res<State<Paused>>) -> ShouldRun + Send + Sync + 'static {
+    move |current: Res<State<S>>, paused: Res<State<Paused>>| {
+        if *current.current() == state && !paused.current().0 {
+            ShouldRun::Yes
+        } else {
+            ShouldRun::No
+        }
+    }
+}
+
+#[derive(Component)]
+struct PauseMenu;
+
+#[derive(Component, Clone, Copy)]
+enum PauseMenuButton {
+    Resume,
+    RestartLevel,
+    LevelSelect,
+    Quit,
+}
+
+fn toggle_pause(input: Res<Input<KeyCode>>, mut paused: ResMut<State<Paused>>) {
+    if input.just_pressed(KeyCode::Escape) {
+        let next = Paused(!paused.current().0);
+        let _ = paused.set(next);
+    }
+}
+
+fn pause_on_focus_lost(
+    mut window_focused_reader: EventReader<WindowFocused>,
+    mut paused: ResMut<State<Paused>>,
+) {
+    for ev in window_focused_reader.iter() {
+        if !ev.focused && !paused.current().0 {
+            let _ = paused.set(Paused(true));
+        }
+    }
+}
+
+fn open_pause_menu(mut commands: Commands, text_details: Res<TextDetails>) {
+    spawn_pause_menu(&mut commands, &text_details);
+}
+
+pub fn spawn_pause_menu(commands: &mut Commands, text_details: &TextDetails) {
+    let text_style = TextStyle {
+        font_size: 30.,
+        ..text_details.text_style.clone().unwrap()
+    };
+
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                position_type: PositionType::Absolute,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                flex_direction: FlexDirection::ColumnReverse,
+                ..default()
+            },
+            color: Color::rgba(0.9, 0.9, 1.0, 0.8).into(),
+            ..default()
+        })
+        .insert(PauseMenu)
+        .with_children(|parent| {
+            parent.spawn_bundle(TextBundle::from_section(
+                "Paused",
+                TextStyle {
+                    font_size: 50.,
+                    ..text_style.clone()
+                },
+            ));
+            spawn_menu_button(
+                parent,
+                PauseMenuButton::Resume,
+                "Resume",
+                text_style.clone(),
+            );
+            spawn_menu_button(
+                parent,
+                PauseMenuButton::RestartLevel,
+                "Restart level",
+                text_style.clone(),
+            );
+            spawn_menu_button(
+                parent,
+                PauseMenuButton::LevelSelect,
+                "Level select",
+                text_style.clone(),
+            );
+            spawn_menu_button(parent, PauseMenuButton::Quit, "Quit", text_style);
+        });
+}
+
+fn despawn_pause_menu(mut commands: Commands, pause_menu_query: Query<Entity, With<PauseMenu>>) {
+    for menu in pause_menu_query.iter() {
+        commands.entity(menu).despawn_recursive();
+    }
+}
+
+fn pause_menu_buttons(
+    mut commands: Commands,
+    interaction_query: Query<(&Interaction, &PauseMenuButton), Changed<Interaction>>,
+    pause_menu_query: Query<Entity, With<PauseMenu>>,
+    mut paused: ResMut<State<Paused>>,
+    mut phase: ResMut<State<Phase>>,
+    mut change_level_writer: EventWriter<ChangeLevel>,
+    mut open_level_select_writer: EventWriter<OpenLevelSelect>,
+    mut exit: EventWriter<AppExit>,
+) {
+    for (interaction, button) in interaction_query.iter() {
+        if *interaction != Interaction::Clicked {
+            continue;
+        }
+
+        match button {
+            PauseMenuButton::Resume => {}
+            PauseMenuButton::RestartLevel => {
+                if *phase.current() == Phase::COMBAT {
+                    let _ = phase.set(Phase::PREP);
+                }
+                change_level_writer.send(ChangeLevel::Restart);
+            }
+            // stays paused while the level select is up
+            PauseMenuButton::LevelSelect => {
+                for menu in pause_menu_query.iter() {
+                    commands.entity(menu).despawn_recursive();
+                }
+                open_level_select_writer.send(OpenLevelSelect);
+                continue;
+            }
+            PauseMenuButton::Quit => exit.send(AppExit),
+        }
+        let _ = paused.set(Paused(false));
+    }
+}
+
+pub struct PausePlugin;
+impl Plugin for PausePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(BasePlugin)
+            .add_system(toggle_pause)
+            .add_system(pause_on_focus_lost)
+            .add_system_set(SystemSet::on_enter(Paused(true)).with_system(open_pause_menu))
+            .add_system_set(SystemSet::on_update(Paused(true)).with_system(pause_menu_buttons))
+            .add_system_set(SystemSet::on_exit(Paused(true)).with_system(despawn_pause_menu));
+    }
+}
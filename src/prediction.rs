@@ -0,0 +1,112 @@
+use bevy::prelude::*;
+
+use crate::{
+    combat::{self, battle_lines, Outcome, Phase},
+    formation::InSlot,
+    hud::{setup_text_details, TextDetails},
+    orb::{EnemyOrb, Orb, PlayerOrb, Stat},
+    pause::unpaused,
+    BasePlugin,
+};
+
+// hard mode hides the prediction
+pub struct Prediction {
+    pub enabled: bool,
+}
+
+impl Default for Prediction {
+    fn default() -> Self {
+        Prediction { enabled: true }
+    }
+}
+
+#[derive(Component)]
+struct PredictionText;
+
+fn setup_prediction_text(mut commands: Commands, text_details: Res<TextDetails>) {
+    commands
+        .spawn_bundle(
+            TextBundle::from_section(
+                "",
+                TextStyle {
+                    font_size: 24.,
+                    ..text_details.text_style.clone().unwrap()
+                },
+            )
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    left: Val::Px(10.0),
+                    bottom: Val::Px(10.0),
+                    ..default()
+                },
+                ..default()
+            }),
+        )
+        .insert(PredictionText);
+}
+
+fn toggle_prediction(input: Res<Input<KeyCode>>, mut prediction: ResMut<Prediction>) {
+    if input.just_pressed(KeyCode::H) {
+        prediction.enabled = !prediction.enabled;
+    }
+}
+
+// resolves the battle as the orbs stand right now, every frame, so it follows drags and merges
+fn update_prediction(
+    prediction: Res<Prediction>,
+    phase: Res<State<Phase>>,
+    player_orb_query: Query<(Entity, &Transform, &Orb, Option<&InSlot>), With<PlayerOrb>>,
+    enemy_orb_query: Query<(Entity, &Transform, &Orb), With<EnemyOrb>>,
+    mut text_query: Query<(&mut Text, &mut Visibility), With<PredictionText>>,
+) {
+    let (mut text, mut visibility) = text_query.single_mut();
+    let (players, enemies) = battle_lines(&player_orb_query, &enemy_orb_query);
+
+    let visible = prediction.enabled
+        && *phase.current() == Phase::PREP
+        && !players.is_empty()
+        && !enemies.is_empty();
+    if visibility.is_visible != visible {
+        visibility.is_visible = visible;
+    }
+    if !visible {
+        return;
+    }
+
+    let player_orbs: Vec<Orb> = players.into_iter().map(|(_, orb)| orb).collect();
+    let enemy_orbs: Vec<Orb> = enemies.into_iter().map(|(_, orb)| orb).collect();
+    let log = combat::resolve(&player_orbs, &enemy_orbs);
+
+    let value = match log.outcome {
+        Outcome::PlayerWon => {
+            let survivors: Vec<String> = log.survivors.iter().map(|orb| orb.to_string()).collect();
+            let remaining_health: Stat = log.survivors.iter().map(|orb| orb.health).sum();
+            format!(
+                "Prediction: WIN | survivors: {} | remaining health: {} (H to hide)",
+                if survivors.is_empty() {
+                    "none".to_string()
+                } else {
+                    survivors.join(", ")
+                },
+                remaining_health,
+            )
+        }
+        Outcome::EnemyWon => "Prediction: LOSE (H to hide)".to_string(),
+        Outcome::Stalemate => "Prediction: STALEMATE, nobody can do damage (H to hide)".to_string(),
+    };
+    if text.sections[0].value != value {
+        text.sections[0].value = value;
+    }
+}
+
+pub struct PredictionPlugin;
+impl Plugin for PredictionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(BasePlugin)
+            .init_resource::<Prediction>()
+            .add_startup_system(setup_prediction_text.after(setup_text_details))
+            .add_system(toggle_prediction.with_run_criteria(unpaused))
+            .add_system(update_prediction);
+    }
+}
@@ -0,0 +1,115 @@
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{orb::Stat, BasePlugin};
+
+const SAVE_FILE: &str = "save.ron";
+
+// more surviving orbs beats more leftover health
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct LevelResult {
+    pub survivors: usize,
+    pub remaining_health: Stat,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct SaveData {
+    // index of the level to continue from
+    pub current_level: usize,
+    pub highest_unlocked: usize,
+    #[serde(default)]
+    pub best_results: BTreeMap<usize, LevelResult>,
+}
+
+impl SaveData {
+    pub fn record_win(&mut self, level: usize, result: LevelResult) {
+        let best = self.best_results.entry(level).or_insert(result);
+        if result > *best {
+            *best = result;
+        }
+    }
+}
+
+// the save and anything else the game writes out lives here
+#[cfg(not(target_arch = "wasm32"))]
+pub fn data_dir() -> Option<std::path::PathBuf> {
+    directories::ProjectDirs::from("", "", "Orber").map(|dirs| dirs.data_dir().to_path_buf())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod storage {
+    use std::{fs, path::PathBuf};
+
+    use super::{data_dir, SAVE_FILE};
+
+    fn save_path() -> Option<PathBuf> {
+        data_dir().map(|dir| dir.join(SAVE_FILE))
+    }
+
+    pub fn read() -> Option<String> {
+        fs::read_to_string(save_path()?).ok()
+    }
+
+    pub fn write(contents: &str) -> Result<(), String> {
+        let path = save_path().ok_or("no data directory on this platform")?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        fs::write(path, contents).map_err(|e| e.to_string())
+    }
+}
+
+// there is no file system in the browser, local storage stands in for it
+#[cfg(target_arch = "wasm32")]
+mod storage {
+    use super::SAVE_FILE;
+
+    fn local_storage() -> Option<web_sys::Storage> {
+        web_sys::window()?.local_storage().ok()?
+    }
+
+    pub fn read() -> Option<String> {
+        local_storage()?.get_item(SAVE_FILE).ok()?
+    }
+
+    pub fn write(contents: &str) -> Result<(), String> {
+        local_storage()
+            .ok_or("local storage is not available")?
+            .set_item(SAVE_FILE, contents)
+            .map_err(|e| format!("{:?}", e))
+    }
+}
+
+fn load_save() -> SaveData {
+    match storage::read() {
+        Some(contents) => ron::de::from_str(&contents).unwrap_or_else(|e| {
+            warn!("ignoring unreadable save file: {}", e);
+            SaveData::default()
+        }),
+        None => SaveData::default(),
+    }
+}
+
+fn write_save(save: Res<SaveData>) {
+    if !save.is_changed() || save.is_added() {
+        return;
+    }
+
+    let result = ron::ser::to_string_pretty(&*save, ron::ser::PrettyConfig::default())
+        .map_err(|e| e.to_string())
+        .and_then(|contents| storage::write(&contents));
+    if let Err(e) = result {
+        warn!("could not write save file: {}", e);
+    }
+}
+
+pub struct SavePlugin;
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(BasePlugin)
+            .insert_resource(load_save())
+            .add_system(write_save);
+    }
+}
@@ -0,0 +1,357 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+};
+
+use crate::{
+    combat,
+    orb::{MergeRule, Orb, Stat, StatCaps},
+};
+
+type Stats = (Stat, Stat);
+
+// groups of atoms are kept as bits of a u32, with every bit set meaning all of them
+pub const MAX_ATOMS: usize = u32::BITS as usize - 1;
+
+fn stats(orb: &Orb) -> Stats {
+    (orb.damage, orb.health)
+}
+
+#[derive(Clone, Debug)]
+pub struct Solution {
+    // front of the line first, each orb keeps the merge tree it was built from
+    pub line_up: Vec<Orb>,
+    // splits and merges needed to get there from the starting pool
+    pub actions: usize,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct SolveReport {
+    pub minimal: Option<Solution>,
+    // winning line-ups, told apart by the stats of their orbs in order
+    pub solutions: usize,
+    pub line_ups_checked: usize,
+}
+
+impl SolveReport {
+    pub fn winnable(&self) -> bool {
+        self.minimal.is_some()
+    }
+}
+
+// the player pool comes apart into more orbs than the search can track
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TooManyAtoms {
+    pub atoms: usize,
+}
+
+impl fmt::Display for TooManyAtoms {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "the player orbs split into {} orbs, the solver handles at most {}",
+            self.atoms, MAX_ATOMS
+        )
+    }
+}
+
+/// Searches every way the player pool can be split, merged under `rule`
+/// without passing `caps`, and lined up against `enemy_orbs`. Fails up front
+/// when the pool splits into more than `MAX_ATOMS` orbs.
+pub fn solve(
+    player_orbs: &[Orb],
+    enemy_orbs: &[Orb],
+    rule: MergeRule,
+    caps: StatCaps,
+) -> Result<SolveReport, TooManyAtoms> {
+    let atoms: usize = player_orbs.iter().map(Orb::leaf_count).sum();
+    if atoms > MAX_ATOMS {
+        return Err(TooManyAtoms { atoms });
+    }
+
+    let mut search = Search {
+        enemy_orbs,
+        rule,
+        caps,
+        outcomes: HashMap::new(),
+        report: SolveReport::default(),
+    };
+
+    // every combination of how far each starting orb gets split
+    let mut pools: Vec<(Vec<Orb>, usize)> = vec![(vec![], 0)];
+    for orb in player_orbs {
+        let mut next = vec![];
+        for (atoms, splits) in &pools {
+            for (cut, cut_splits) in cuts(orb) {
+                let mut atoms = atoms.clone();
+                atoms.extend(cut);
+                next.push((atoms, splits + cut_splits));
+            }
+        }
+        pools = next;
+    }
+
+    for (atoms, splits) in pools {
+        search.atoms(&atoms, splits);
+    }
+
+    search.report.solutions = search.outcomes.values().filter(|won| **won).count();
+    Ok(search.report)
+}
+
+// an orb can be kept whole, or split and each child cut again
+fn cuts(orb: &Orb) -> Vec<(Vec<Orb>, usize)> {
+    let mut options = vec![(vec![orb.clone()], 0)];
+    if orb.children.is_empty() {
+        return options;
+    }
+
+    let mut children: Vec<(Vec<Orb>, usize)> = vec![(vec![], 1)];
+    for child in &orb.children {
+        let mut next = vec![];
+        for (atoms, splits) in &children {
+            for (cut, cut_splits) in cuts(child) {
+                let mut atoms = atoms.clone();
+                atoms.extend(cut);
+                next.push((atoms, splits + cut_splits));
+            }
+        }
+        children = next;
+    }
+    options.extend(children);
+    options
+}
+
+struct Search<'a> {
+    enemy_orbs: &'a [Orb],
+    rule: MergeRule,
+    caps: StatCaps,
+    outcomes: HashMap<Vec<Stats>, bool>,
+    report: SolveReport,
+}
+
+impl Search<'_> {
+    fn atoms(&mut self, atoms: &[Orb], splits: usize) {
+        let full = (1u32 << atoms.len()) - 1;
+        let mut merged: HashMap<u32, Vec<Orb>> = HashMap::new();
+        let mut groups = vec![];
+        self.partitions(atoms, full, &mut merged, &mut groups, splits);
+    }
+
+    // splits `remaining` into groups of atoms that get merged into one orb each
+    fn partitions(
+        &mut self,
+        atoms: &[Orb],
+        remaining: u32,
+        merged: &mut HashMap<u32, Vec<Orb>>,
+        groups: &mut Vec<u32>,
+        splits: usize,
+    ) {
+        if remaining == 0 {
+            let options: Vec<Vec<Orb>> = groups
+                .iter()
+                .map(|&group| merge_trees(atoms, group, self.rule, self.caps, merged))
+                .collect();
+            let actions = splits + atoms.len() - groups.len();
+            self.choices(&options, &mut vec![], actions);
+            return;
+        }
+
+        // the lowest remaining atom always starts the next group, so each
+        // partition is only visited once
+        let lowest = remaining & remaining.wrapping_neg();
+        let rest = remaining & !lowest;
+        let mut subset = rest;
+        loop {
+            groups.push(subset | lowest);
+            self.partitions(atoms, rest & !subset, merged, groups, splits);
+            groups.pop();
+
+            if subset == 0 {
+                break;
+            }
+            subset = (subset - 1) & rest;
+        }
+    }
+
+    fn choices(&mut self, options: &[Vec<Orb>], chosen: &mut Vec<Orb>, actions: usize) {
+        if chosen.len() == options.len() {
+            let mut used = vec![false; chosen.len()];
+            self.line_ups(chosen, &mut used, &mut vec![], actions);
+            return;
+        }
+
+        for orb in &options[chosen.len()] {
+            chosen.push(orb.clone());
+            self.choices(options, chosen, actions);
+            chosen.pop();
+        }
+    }
+
+    fn line_ups(
+        &mut self,
+        orbs: &[Orb],
+        used: &mut [bool],
+        line_up: &mut Vec<Orb>,
+        actions: usize,
+    ) {
+        if line_up.len() == orbs.len() {
+            self.check(line_up, actions);
+            return;
+        }
+
+        let mut tried = HashSet::new();
+        for (i, orb) in orbs.iter().enumerate() {
+            if used[i] || !tried.insert(stats(orb)) {
+                continue;
+            }
+            used[i] = true;
+            line_up.push(orb.clone());
+            self.line_ups(orbs, used, line_up, actions);
+            line_up.pop();
+            used[i] = false;
+        }
+    }
+
+    fn check(&mut self, line_up: &[Orb], actions: usize) {
+        let key: Vec<Stats> = line_up.iter().map(stats).collect();
+        let enemy_orbs = self.enemy_orbs;
+        let mut checked = false;
+        let won = *self.outcomes.entry(key).or_insert_with(|| {
+            checked = true;
+            combat::resolve(line_up, enemy_orbs).won()
+        });
+        if checked {
+            self.report.line_ups_checked += 1;
+        }
+
+        if won && !matches!(&self.report.minimal, Some(minimal) if minimal.actions <= actions) {
+            self.report.minimal = Some(Solution {
+                line_up: line_up.to_vec(),
+                actions,
+            });
+        }
+    }
+}
+
+// every orb a group of atoms can be merged into, one merge tree per distinct result
+fn merge_trees(
+    atoms: &[Orb],
+    group: u32,
+    rule: MergeRule,
+    caps: StatCaps,
+    merged: &mut HashMap<u32, Vec<Orb>>,
+) -> Vec<Orb> {
+    if let Some(orbs) = merged.get(&group) {
+        return orbs.clone();
+    }
+
+    let orbs = if group.count_ones() == 1 {
+        vec![atoms[group.trailing_zeros() as usize].clone()]
+    } else {
+        let lowest = group & group.wrapping_neg();
+        let rest = group & !lowest;
+        let mut seen = HashSet::new();
+        let mut orbs = vec![];
+        // `left` always holds the lowest atom so each split is only tried once
+        let mut subset = rest;
+        while subset != 0 {
+            let left = (rest & !subset) | lowest;
+            let right = subset;
+            for a in merge_trees(atoms, left, rule, caps, merged) {
+                for b in merge_trees(atoms, right, rule, caps, merged) {
+                    // merges the game would refuse aren't part of any solution
+                    if let Ok(orb) = Orb::try_merge(&a, &b, rule, caps) {
+                        if seen.insert(stats(&orb)) {
+                            orbs.push(orb);
+                        }
+                    }
+                }
+            }
+            subset = (subset - 1) & rest;
+        }
+        orbs
+    };
+
+    merged.insert(group, orbs.clone());
+    orbs
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::Path};
+
+    use super::*;
+    use crate::level::Level;
+
+    // `path` is relative to the crate root
+    fn solve_level(path: &str) -> SolveReport {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join(path);
+        let level: Level = ron::de::from_bytes(&fs::read(&path).unwrap()).unwrap();
+        let player_orbs: Vec<Orb> = level.player_orbs.iter().map(|o| o.orb.clone()).collect();
+        let enemy_orbs: Vec<Orb> = level.enemy_orbs.iter().map(|o| o.orb.clone()).collect();
+        solve(&player_orbs, &enemy_orbs, level.merge_rule, level.stat_caps).unwrap()
+    }
+
+    fn line_up(report: &SolveReport) -> Vec<(i32, i32)> {
+        report
+            .minimal
+            .as_ref()
+            .unwrap()
+            .line_up
+            .iter()
+            .map(|orb| (orb.damage.0, orb.health.0))
+            .collect()
+    }
+
+    fn orb(damage: i32, health: i32) -> Orb {
+        Orb {
+            damage: Stat(damage),
+            health: Stat(health),
+            ..Orb::default()
+        }
+    }
+
+    #[test]
+    fn level_2_is_won_as_dealt() {
+        let report = solve_level("assets/levels/level_2.level.ron");
+        assert!(report.winnable());
+        // the last 1/1 takes the 3/3 down with it, which still counts as a win
+        assert_eq!(line_up(&report), vec![(1, 1); 4]);
+        assert_eq!(report.minimal.unwrap().actions, 0);
+    }
+
+    #[test]
+    fn level_3_is_won_as_dealt() {
+        let report = solve_level("assets/levels/level_3.level.ron");
+        assert!(report.winnable());
+        assert_eq!(line_up(&report), vec![(2, 3), (3, -2)]);
+        assert_eq!(report.minimal.unwrap().actions, 0);
+    }
+
+    #[test]
+    fn merge_tax_only_wins_with_everything_merged() {
+        // not part of the campaign, it's only here to pin down the Tax rule
+        let report = solve_level("tests/fixtures/merge_tax.level.ron");
+        assert_eq!(line_up(&report), vec![(6, 5)]);
+        assert_eq!(report.minimal.unwrap().actions, 2);
+        assert_eq!(report.solutions, 1);
+    }
+
+    #[test]
+    fn pools_past_the_atom_limit_are_rejected() {
+        let pool = vec![orb(1, 1); MAX_ATOMS + 1];
+        let result = solve(
+            &pool,
+            &[orb(1, 1)],
+            MergeRule::Additive,
+            StatCaps::default(),
+        );
+        assert_eq!(
+            result.unwrap_err(),
+            TooManyAtoms {
+                atoms: MAX_ATOMS + 1
+            }
+        );
+    }
+}
@@ -0,0 +1,71 @@
+mod harness;
+
+use bevy::prelude::*;
+use bevy_github_ci_template::orb::Orb;
+use harness::Harness;
+
+fn stats(orbs: &[(Vec2, Orb)]) -> Vec<(i32, i32)> {
+    let mut stats: Vec<(i32, i32)> = orbs
+        .iter()
+        .map(|(_, orb)| (orb.damage.0, orb.health.0))
+        .collect();
+    stats.sort();
+    stats
+}
+
+#[test]
+fn merging_two_orbs_on_level_2_makes_a_2_2_orb() {
+    let mut harness = Harness::new();
+    harness.goto_level(2);
+    assert_eq!(stats(&harness.player_orbs()), vec![(1, 1); 4]);
+
+    let orbs = harness.player_orbs();
+    harness.drag(orbs[0].0, orbs[1].0);
+    harness.settle();
+
+    assert_eq!(stats(&harness.player_orbs()), vec![(1, 1), (1, 1), (2, 2)]);
+    let merged: Vec<(i32, i32)> = harness
+        .recorded()
+        .merged
+        .iter()
+        .map(|orb| (orb.damage.0, orb.health.0))
+        .collect();
+    assert_eq!(merged, vec![(2, 2)]);
+}
+
+#[test]
+fn level_0_is_won_as_dealt() {
+    let mut harness = Harness::new();
+    harness.goto_level(0);
+    harness.fight();
+
+    assert_eq!(harness.recorded().finished, vec![true]);
+    assert_eq!(harness.current_level(), 1);
+}
+
+#[test]
+fn level_1_is_lost_without_merging_and_restarts() {
+    let mut harness = Harness::new();
+    harness.goto_level(1);
+    harness.fight();
+
+    assert_eq!(harness.recorded().finished, vec![false]);
+    assert_eq!(harness.current_level(), 1);
+    harness.frames(30);
+    assert_eq!(stats(&harness.player_orbs()), vec![(1, 4), (4, 1)]);
+}
+
+#[test]
+fn level_1_is_won_once_both_orbs_are_merged() {
+    let mut harness = Harness::new();
+    harness.goto_level(1);
+
+    let orbs = harness.player_orbs();
+    harness.drag(orbs[0].0, orbs[1].0);
+    harness.settle();
+    assert_eq!(stats(&harness.player_orbs()), vec![(5, 5)]);
+
+    harness.fight();
+    assert_eq!(harness.recorded().finished, vec![true]);
+    assert_eq!(harness.current_level(), 2);
+}
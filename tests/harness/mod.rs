@@ -0,0 +1,224 @@
+use std::time::{Duration, Instant};
+
+use bevy::{
+    asset::AssetPlugin,
+    ecs::event::Events,
+    hierarchy::HierarchyPlugin,
+    input::{keyboard::KeyboardInput, mouse::MouseButtonInput, ButtonState, InputPlugin},
+    prelude::*,
+    time::TimePlugin,
+    transform::TransformPlugin,
+    window::WindowFocused,
+};
+use bevy_github_ci_template::{
+    combat::{CombatFinished, Phase},
+    cursor::{Cursor, CursorPlugin},
+    hud::HudPlugin,
+    level::{CampaignLevels, ChangeLevel, CurrentLevel},
+    orb::{Orb, OrbMerged, PlayerOrb},
+    orb_animation::Animating,
+    save::SavePlugin,
+    CorePlugins,
+};
+
+// every frame advances the clock by exactly this much, however long it took to run
+const FRAME: Duration = Duration::from_millis(16);
+
+// events seen since the harness started, they'd be gone by the time a test looks otherwise
+#[derive(Default)]
+pub struct Recorded {
+    pub merged: Vec<Orb>,
+    pub finished: Vec<bool>,
+}
+
+fn record(
+    mut recorded: ResMut<Recorded>,
+    mut merged_reader: EventReader<OrbMerged>,
+    mut finished_reader: EventReader<CombatFinished>,
+) {
+    recorded
+        .merged
+        .extend(merged_reader.iter().map(|merged| merged.orb.clone()));
+    recorded
+        .finished
+        .extend(finished_reader.iter().map(|finished| finished.won));
+}
+
+/// The game without a window, renderer or save file. The cursor, mouse
+/// buttons, keys and clock are scripted a frame at a time.
+pub struct Harness {
+    pub app: App,
+    now: Instant,
+}
+
+impl Harness {
+    pub fn new() -> Self {
+        let mut app = App::new();
+        // `Time` is stepped by `frame` instead
+        app.add_plugins_with(MinimalPlugins, |group| group.disable::<TimePlugin>())
+            .init_resource::<Time>()
+            .add_plugin(AssetPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(HierarchyPlugin)
+            .add_plugin(InputPlugin)
+            // sprite materials, normally added by the render plugins
+            .add_asset::<ColorMaterial>()
+            .add_event::<WindowFocused>()
+            // the hud and cursor need a window, and the save would touch the disk
+            .add_plugins_with(CorePlugins, |group| {
+                group
+                    .disable::<HudPlugin>()
+                    .disable::<CursorPlugin>()
+                    .disable::<SavePlugin>()
+            })
+            .init_resource::<Recorded>()
+            // after every gameplay system, so events sent on the frame `settle` stops are kept
+            .add_system_to_stage(CoreStage::PostUpdate, record);
+
+        let mut harness = Harness {
+            app,
+            now: Instant::now(),
+        };
+        let loaded = harness.run_until(300, |world| {
+            world
+                .resource::<CampaignLevels>()
+                .is_loaded(world.resource::<AssetServer>())
+        });
+        assert!(loaded, "campaign levels never finished loading");
+        harness
+    }
+
+    pub fn frame(&mut self) {
+        self.now += FRAME;
+        self.app
+            .world
+            .resource_mut::<Time>()
+            .update_with_instant(self.now);
+        self.app.update();
+    }
+
+    pub fn frames(&mut self, count: usize) {
+        for _ in 0..count {
+            self.frame();
+        }
+    }
+
+    // false if `condition` still doesn't hold after `max_frames`
+    pub fn run_until(
+        &mut self,
+        max_frames: usize,
+        mut condition: impl FnMut(&World) -> bool,
+    ) -> bool {
+        for _ in 0..max_frames {
+            if condition(&self.app.world) {
+                return true;
+            }
+            self.frame();
+        }
+        condition(&self.app.world)
+    }
+
+    pub fn goto_level(&mut self, index: usize) {
+        self.app
+            .world
+            .resource_mut::<Events<ChangeLevel>>()
+            .send(ChangeLevel::Goto(index));
+        // orbs spawn from commands, then get pulled into their slots
+        self.frames(30);
+    }
+
+    // waits out merges and splits, combat and undo won't start until they're over
+    pub fn settle(&mut self) {
+        for _ in 0..120 {
+            let mut animating = self.app.world.query_filtered::<Entity, With<Animating>>();
+            if animating.iter(&self.app.world).next().is_none() {
+                return;
+            }
+            self.frame();
+        }
+        panic!("orbs were still animating");
+    }
+
+    pub fn move_cursor(&mut self, position: Vec2) {
+        self.app.world.resource_mut::<Cursor>().0 = position;
+    }
+
+    pub fn mouse(&mut self, button: MouseButton, state: ButtonState) {
+        self.app
+            .world
+            .resource_mut::<Events<MouseButtonInput>>()
+            .send(MouseButtonInput { button, state });
+        self.frame();
+    }
+
+    pub fn key(&mut self, key_code: KeyCode, state: ButtonState) {
+        self.app
+            .world
+            .resource_mut::<Events<KeyboardInput>>()
+            .send(KeyboardInput {
+                scan_code: 0,
+                key_code: Some(key_code),
+                state,
+            });
+        self.frame();
+    }
+
+    pub fn tap(&mut self, key_code: KeyCode) {
+        self.key(key_code, ButtonState::Pressed);
+        self.key(key_code, ButtonState::Released);
+    }
+
+    /// Picks up the orb under `from` and drops it at `to`, moving the cursor
+    /// there over a few frames like a hand would.
+    pub fn drag(&mut self, from: Vec2, to: Vec2) {
+        self.move_cursor(from);
+        self.frames(2);
+        self.mouse(MouseButton::Left, ButtonState::Pressed);
+        for step in 1..=20 {
+            self.move_cursor(from.lerp(to, step as f32 / 20.0));
+            self.frame();
+        }
+        // the orb trails the cursor a little
+        self.frames(30);
+        self.mouse(MouseButton::Left, ButtonState::Released);
+    }
+
+    // starts combat and skips straight to the result
+    pub fn fight(&mut self) {
+        self.settle();
+        let fights = self.recorded().finished.len();
+        self.tap(KeyCode::Space);
+        assert_eq!(
+            *self.app.world.resource::<State<Phase>>().current(),
+            Phase::COMBAT
+        );
+        self.tap(KeyCode::S);
+        let finished = self.run_until(300, |world| {
+            world.resource::<Recorded>().finished.len() > fights
+        });
+        assert!(finished, "combat never finished");
+        // the next level is spawned from the `ChangeLevel` sent at the end
+        self.frames(2);
+    }
+
+    // front of the formation first
+    pub fn player_orbs(&mut self) -> Vec<(Vec2, Orb)> {
+        let mut orbs: Vec<(Vec2, Orb)> = self
+            .app
+            .world
+            .query_filtered::<(&Transform, &Orb), With<PlayerOrb>>()
+            .iter(&self.app.world)
+            .map(|(t, orb)| (t.translation.truncate(), orb.clone()))
+            .collect();
+        orbs.sort_by(|a, b| b.0.x.total_cmp(&a.0.x));
+        orbs
+    }
+
+    pub fn current_level(&self) -> usize {
+        self.app.world.resource::<CurrentLevel>().0
+    }
+
+    pub fn recorded(&self) -> &Recorded {
+        self.app.world.resource::<Recorded>()
+    }
+}